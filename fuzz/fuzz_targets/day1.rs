@@ -0,0 +1,13 @@
+#![no_main]
+
+use adventofcode2023::day1::{self, Combine, Vocabulary};
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    let _ = day1::part1_bytes(input);
+    let _ = day1::part2_bytes(input);
+    for combine in [Combine::Sum, Combine::Product] {
+        let _ = day1::calibrate_bytes(input, &Vocabulary::english(), combine);
+    }
+});
@@ -0,0 +1,31 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::day1::{self, Combine, Vocabulary};
+use libfuzzer_sys::fuzz_target;
+
+// A vocabulary file, then a NUL, then the calibration lines. Errors are fine,
+// panics are not, and every way of calibrating must agree.
+fuzz_target!(|input: &[u8]| {
+    let (spec, lines) = match input.iter().position(|c| *c == 0) {
+        Some(nul) => (&input[..nul], &input[nul + 1..]),
+        None => (input, &b""[..]),
+    };
+    let mut vocabularies: Vec<Vocabulary> = ["digits", "english", "french", "roman"]
+        .iter()
+        .filter_map(|name| Vocabulary::builtin(name))
+        .collect();
+    vocabularies.extend(String::from_utf8_lossy(spec).parse::<Vocabulary>());
+
+    for vocabulary in vocabularies.iter() {
+        for combine in [Combine::Concat, Combine::Sum, Combine::Product] {
+            let in_memory = day1::calibrate_bytes(lines, vocabulary, combine);
+            let streamed =
+                day1::calibrate_reader(BufReader::with_capacity(5, lines), vocabulary, combine);
+            let parallel = day1::calibrate_parallel(lines, vocabulary, combine, 3);
+            assert_eq!(in_memory, streamed);
+            assert_eq!(in_memory, parallel);
+        }
+    }
+});
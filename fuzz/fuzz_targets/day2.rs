@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::day2::{self, Bag};
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    let _ = day2::part1_reader(BufReader::new(input), &Bag::default());
+    let _ = day2::part2_reader(BufReader::new(input));
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::day2::{self, Bag};
+use libfuzzer_sys::fuzz_target;
+
+// A bag spec, then a NUL, then the games. Errors are fine, panics are not.
+fuzz_target!(|input: &[u8]| {
+    let (spec, games) = match input.iter().position(|c| *c == 0) {
+        Some(nul) => (&input[..nul], &input[nul + 1..]),
+        None => (input, &b""[..]),
+    };
+    let Ok(bag) = String::from_utf8_lossy(spec).parse::<Bag>() else {
+        return;
+    };
+    let _ = day2::part1_reader(BufReader::with_capacity(5, games), &bag);
+    if let Ok(reports) = day2::report_str(&String::from_utf8_lossy(games), &bag) {
+        let _ = day2::ruled_out_by_color(&reports, &bag);
+        for report in reports.iter() {
+            let _ = report.to_string();
+        }
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use adventofcode2023::day3;
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    let _ = day3::part1_bytes(input);
+    let _ = day3::part2_bytes(input);
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use adventofcode2023::day3::{self, Rules};
+use libfuzzer_sys::fuzz_target;
+
+// Rules on the first line, then the schematic. Errors are fine, panics are
+// not.
+fuzz_target!(|input: &[u8]| {
+    let (spec, schematic) = match input.iter().position(|c| *c == b'\n') {
+        Some(newline) => (&input[..newline], &input[newline + 1..]),
+        None => (input, &b""[..]),
+    };
+    if let Ok(rules) = String::from_utf8_lossy(spec).parse::<Rules>() {
+        let _ = day3::score_bytes(schematic, &rules);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::day4::{self, PastEnd};
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    let _ = day4::part1_reader(BufReader::new(input));
+    let _ = day4::part2_reader(BufReader::new(input), PastEnd::Clamp);
+    let _ = day4::part2_reader(BufReader::new(input), PastEnd::Error);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::day7;
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    let _ = day7::part1_reader(BufReader::new(input));
+    let _ = day7::part2_reader(BufReader::new(input));
+});
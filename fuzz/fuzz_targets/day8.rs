@@ -0,0 +1,12 @@
+#![no_main]
+
+use adventofcode2023::day8;
+use libfuzzer_sys::fuzz_target;
+
+// Errors are fine, panics are not
+fuzz_target!(|input: &[u8]| {
+    // Invalid UTF-8 becomes U+FFFD, which the parser still has to reject
+    let input = &String::from_utf8_lossy(input);
+    let _ = day8::part1_str(input);
+    let _ = day8::part2_str(input);
+});
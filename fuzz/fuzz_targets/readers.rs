@@ -0,0 +1,38 @@
+#![no_main]
+
+use std::io::BufReader;
+
+use adventofcode2023::{day1, day2, day4, day7};
+use libfuzzer_sys::fuzz_target;
+
+// Every streaming reader on raw bytes, through a buffer small enough that
+// lines span reads. Errors are fine, panics are not, and whenever there is an
+// in-memory entry point for the input the two must agree.
+fuzz_target!(|input: &[u8]| {
+    let reader = || BufReader::with_capacity(5, input);
+    let bag = day2::Bag::default();
+    let past_end = day4::PastEnd::Error;
+
+    assert_eq!(day1::part1_reader(reader()), day1::part1_bytes(input));
+    assert_eq!(day1::part2_reader(reader()), day1::part2_bytes(input));
+
+    let streamed = (
+        day2::part1_reader(reader(), &bag),
+        day2::part2_reader(reader()),
+        day4::part1_reader(reader()),
+        day4::part2_reader(reader(), past_end),
+        day7::part1_reader(reader()),
+        day7::part2_reader(reader()),
+    );
+    if let Ok(input) = std::str::from_utf8(input) {
+        let in_memory = (
+            day2::part1_str(input, &bag),
+            day2::part2_str(input),
+            day4::part1_str(input),
+            day4::part2_str(input, past_end),
+            day7::part1_str(input),
+            day7::part2_str(input),
+        );
+        assert_eq!(streamed, in_memory);
+    }
+});
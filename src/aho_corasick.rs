@@ -0,0 +1,248 @@
+/// Multi-pattern byte scanner. The automaton is built once from a pattern list
+/// and reports every (possibly overlapping) occurrence in a single pass.
+pub struct AhoCorasick {
+    // Fully resolved transitions, so scanning never follows failure links
+    transitions: Vec<[u32; 256]>,
+    // Pattern ids ending at each state, including those reached by failure links
+    outputs: Vec<Vec<usize>>,
+    pattern_lens: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+const ROOT: u32 = 0;
+const MISSING: u32 = u32::MAX;
+
+impl AhoCorasick {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> AhoCorasick {
+        let mut transitions = vec![[MISSING; 256]];
+        let mut outputs = vec![Vec::new()];
+
+        // Build the trie
+        for (pattern_id, pattern) in patterns.iter().enumerate() {
+            let mut state = ROOT;
+            for byte in pattern.as_ref() {
+                let next = transitions[state as usize][*byte as usize];
+                state = if next == MISSING {
+                    transitions.push([MISSING; 256]);
+                    outputs.push(Vec::new());
+                    let new_state = (transitions.len() - 1) as u32;
+                    transitions[state as usize][*byte as usize] = new_state;
+                    new_state
+                } else {
+                    next
+                };
+            }
+            outputs[state as usize].push(pattern_id);
+        }
+
+        // Breadth first, so each state's failure target is finished before it
+        let mut fail = vec![ROOT; transitions.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for slot in transitions[ROOT as usize].iter_mut() {
+            match *slot {
+                MISSING => *slot = ROOT,
+                child => queue.push_back(child),
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let fail_row = transitions[fail[state as usize] as usize];
+            let mut row = transitions[state as usize];
+            for (slot, fail_next) in row.iter_mut().zip(fail_row) {
+                match *slot {
+                    MISSING => *slot = fail_next,
+                    child => {
+                        fail[child as usize] = fail_next;
+                        let inherited = outputs[fail_next as usize].clone();
+                        outputs[child as usize].extend(inherited);
+                        queue.push_back(child);
+                    }
+                }
+            }
+            transitions[state as usize] = row;
+        }
+
+        AhoCorasick {
+            transitions,
+            outputs,
+            pattern_lens: patterns.iter().map(|p| p.as_ref().len()).collect(),
+        }
+    }
+
+    /// Every occurrence of every pattern in `haystack`, ordered by end position.
+    pub fn find_overlapping_iter<'a>(
+        &'a self,
+        haystack: &'a [u8],
+    ) -> impl Iterator<Item = Match> + 'a {
+        haystack
+            .iter()
+            .enumerate()
+            .scan(ROOT, move |state, (pos, byte)| {
+                *state = self.transitions[*state as usize][*byte as usize];
+                Some((pos + 1, &self.outputs[*state as usize]))
+            })
+            .flat_map(move |(end, pattern_ids)| {
+                pattern_ids.iter().map(move |pattern| Match {
+                    pattern: *pattern,
+                    start: end - self.pattern_lens[*pattern],
+                    end,
+                })
+            })
+    }
+
+    /// Non-overlapping matches, read left to right taking the longest match
+    /// at each position, then the lowest pattern id.
+    pub fn find_longest(&self, haystack: &[u8]) -> Vec<Match> {
+        let mut longest: Vec<Option<Match>> = vec![None; haystack.len()];
+        for m in self.find_overlapping_iter(haystack) {
+            let Some(best) = longest.get_mut(m.start) else {
+                continue;
+            };
+            if best.is_none_or(|b| m.end > b.end || (m.end == b.end && m.pattern < b.pattern)) {
+                *best = Some(m);
+            }
+        }
+        let mut found = Vec::new();
+        let mut pos = 0;
+        while pos < haystack.len() {
+            match longest[pos] {
+                Some(m) => {
+                    found.push(m);
+                    pos = m.end.max(pos + 1);
+                }
+                None => pos += 1,
+            }
+        }
+        found
+    }
+
+    /// The leftmost and rightmost starting matches in `haystack`. Matches that
+    /// start at the same position are broken by the longest, then the lowest
+    /// pattern id.
+    pub fn first_and_last(&self, haystack: &[u8]) -> Option<(Match, Match)> {
+        self.find_overlapping_iter(haystack)
+            .fold(None, |found, m| match found {
+                None => Some((m, m)),
+                Some((first, last)) => {
+                    let prefer = |m: &Match, other: &Match| {
+                        m.end > other.end || (m.end == other.end && m.pattern < other.pattern)
+                    };
+                    Some((
+                        if m.start < first.start || (m.start == first.start && prefer(&m, &first)) {
+                            m
+                        } else {
+                            first
+                        },
+                        if m.start > last.start || (m.start == last.start && prefer(&m, &last)) {
+                            m
+                        } else {
+                            last
+                        },
+                    ))
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn matches(patterns: &[&str], haystack: &str) -> Vec<(usize, usize, usize)> {
+        let mut found: Vec<_> = AhoCorasick::new(patterns)
+            .find_overlapping_iter(haystack.as_bytes())
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect();
+        found.sort_unstable_by_key(|(pattern, start, end)| (*end, *start, *pattern));
+        found
+    }
+
+    #[test]
+    fn overlapping_matches_are_all_found() {
+        let digits = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        assert_eq!(matches(&digits, "eightwo"), vec![(7, 0, 5), (1, 4, 7)]);
+        let scanner = AhoCorasick::new(&digits);
+        let (first, last) = scanner.first_and_last(b"xeightwox").unwrap();
+        assert_eq!((first.pattern, last.pattern), (7, 1));
+        assert_eq!(
+            matches(&["aa"], "aaaa"),
+            vec![(0, 0, 2), (0, 1, 3), (0, 2, 4)]
+        );
+    }
+
+    #[test]
+    fn nested_and_suffix_patterns() {
+        // "he" is inside "she" and "hers"
+        assert_eq!(
+            matches(&["he", "she", "hers", "his"], "ushers"),
+            vec![(1, 1, 4), (0, 2, 4), (2, 2, 6)]
+        );
+        // Each pattern is a suffix of the one before
+        assert_eq!(
+            matches(&["abc", "bc", "c"], "abc"),
+            vec![(0, 0, 3), (1, 1, 3), (2, 2, 3)]
+        );
+        let scanner = AhoCorasick::new(&["abc", "bc", "c"]);
+        let (first, last) = scanner.first_and_last(b"abc").unwrap();
+        assert_eq!((first.pattern, last.pattern), (0, 2));
+        // Same start: the longest wins
+        let scanner = AhoCorasick::new(&["a", "ab"]);
+        let (first, last) = scanner.first_and_last(b"ab").unwrap();
+        assert_eq!((first.pattern, last.pattern), (1, 1));
+    }
+
+    #[test]
+    fn empty_input_and_patterns() {
+        assert!(matches(&["one", "two"], "").is_empty());
+        assert!(AhoCorasick::new(&["one"]).first_and_last(b"").is_none());
+        assert!(matches(&[], "one").is_empty());
+    }
+
+    #[test]
+    fn longest_matches_do_not_overlap() {
+        let longest = |patterns: &[&str], haystack: &str| -> Vec<(usize, usize, usize)> {
+            AhoCorasick::new(patterns)
+                .find_longest(haystack.as_bytes())
+                .iter()
+                .map(|m| (m.pattern, m.start, m.end))
+                .collect()
+        };
+        let roman = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+        assert_eq!(longest(&roman, "VIII"), vec![(7, 0, 4)]);
+        assert_eq!(longest(&roman, "XIV"), vec![(9, 0, 1), (3, 1, 3)]);
+        assert_eq!(longest(&roman, "IIII"), vec![(2, 0, 3), (0, 3, 4)]);
+        assert_eq!(
+            longest(&["one", "eight", "two"], "eightwone"),
+            vec![(1, 0, 5), (0, 6, 9)]
+        );
+        assert!(longest(&roman, "").is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn matches_naive_search(
+            patterns in prop::collection::vec("[ab]{1,4}", 1..6),
+            haystack in "[abc]{0,30}",
+        ) {
+            let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+            let mut naive = Vec::new();
+            for (pattern_id, pattern) in patterns.iter().enumerate() {
+                for start in 0..haystack.len() {
+                    if haystack[start..].starts_with(pattern) {
+                        naive.push((pattern_id, start, start + pattern.len()));
+                    }
+                }
+            }
+            naive.sort_unstable_by_key(|(pattern, start, end)| (*end, *start, *pattern));
+            prop_assert_eq!(matches(&patterns, &haystack), naive);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs;
+
+/// Known answers for a day, read from `src/<day>/answers.txt`. Each line is
+/// `<part> <input file> <answer>`, e.g. `part1 part1_sample_input.txt 142`.
+/// Blank lines and lines starting with `#` are skipped.
+#[derive(Debug, Default)]
+pub struct Answers {
+    entries: Vec<(String, String, String)>,
+}
+
+impl Answers {
+    pub fn load(day: &str) -> Result<Answers, String> {
+        let path = format!("src/{}/answers.txt", day);
+        let contents =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+        let mut entries = Vec::new();
+        for (line_idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [part, file, answer] => {
+                    entries.push((part.to_string(), file.to_string(), answer.to_string()))
+                }
+                _ => {
+                    return Err(format!(
+                        "{} line {}: expected <part> <input file> <answer>",
+                        path,
+                        line_idx + 1
+                    ))
+                }
+            }
+        }
+        Ok(Answers { entries })
+    }
+
+    pub fn expected(&self, part: &str, file: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(p, f, _)| p == part && f == file)
+            .map(|(_, _, answer)| answer.as_str())
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt::Display;
+
+use super::{Combine, Vocabulary};
+
+#[derive(Debug)]
+pub struct Token {
+    pub text: String,
+    // Byte offset within the line
+    pub pos: usize,
+    pub value: u64,
+}
+
+#[derive(Debug)]
+pub struct LineExplanation {
+    pub line_num: usize,
+    pub tokens: Option<(Token, Token)>,
+    pub value: u64,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} at {} = {}", self.text, self.pos, self.value)
+    }
+}
+
+impl Display for LineExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.tokens {
+            Some((first, last)) => write!(
+                f,
+                "line {}: first {}, last {} -> {}",
+                self.line_num, first, last, self.value
+            ),
+            None => write!(f, "line {}: NO DIGITS -> {}", self.line_num, self.value),
+        }
+    }
+}
+
+/// Breaks the calibration down line by line. A trailing newline does not start
+/// another line, but a last line without one is still counted.
+pub fn explain(
+    input_path: &str,
+    vocabulary: &Vocabulary,
+    combine: Combine,
+) -> Result<Vec<LineExplanation>, String> {
+    let input = std::fs::read(input_path).map_err(|e| format!("File read error: {}", e))?;
+    explain_bytes(&input, vocabulary, combine)
+}
+
+pub fn explain_bytes(
+    input: &[u8],
+    vocabulary: &Vocabulary,
+    combine: Combine,
+) -> Result<Vec<LineExplanation>, String> {
+    let input = input.strip_suffix(b"\n").unwrap_or(input);
+
+    let scanner = vocabulary.scanner();
+
+    let mut explanations = Vec::new();
+    for (line_idx, line) in input.split(|c| *c == b'\n').enumerate() {
+        let tokens = scanner.first_and_last(line).map(|(first, last)| {
+            let to_token = |m: crate::aho_corasick::Match| Token {
+                text: String::from_utf8_lossy(&line[m.start..m.end]).to_string(),
+                pos: m.start,
+                value: scanner.value(&m),
+            };
+            (to_token(first), to_token(last))
+        });
+        let value = match &tokens {
+            Some((first, last)) => combine
+                .apply(first.value, last.value)
+                .ok_or(format!("Overflow at line {}", line_idx + 1))?,
+            None => 0,
+        };
+        explanations.push(LineExplanation {
+            line_num: line_idx + 1,
+            tokens,
+            value,
+        });
+    }
+    Ok(explanations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::calibrate_bytes;
+    use super::*;
+
+    #[test]
+    fn lines_are_explained() {
+        let english = Vocabulary::english();
+        let input = b"xtwone3four\nnothing\n7";
+        let lines = explain_bytes(input, &english, Combine::Concat).unwrap();
+        let shown: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(
+            shown,
+            [
+                "line 1: first \"two\" at 1 = 2, last \"four\" at 7 = 4 -> 24",
+                "line 2: NO DIGITS -> 0",
+                "line 3: first \"7\" at 0 = 7, last \"7\" at 0 = 7 -> 77",
+            ]
+        );
+        let with_newline = explain_bytes(b"xtwone3four\nnothing\n7\n", &english, Combine::Concat);
+        assert_eq!(with_newline.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn lines_add_up_to_the_calibration() {
+        let sample = std::fs::read("src/day1/part2_sample_input.txt").unwrap();
+        let english = Vocabulary::english();
+        for combine in [Combine::Concat, Combine::Sum, Combine::Product] {
+            let lines = explain_bytes(&sample, &english, combine).unwrap();
+            assert_eq!(
+                Ok(lines.iter().map(|line| line.value).sum()),
+                calibrate_bytes(&sample, &english, combine)
+            );
+        }
+    }
+}
@@ -1,75 +1,205 @@
-fn char_to_num(char: Option<u8>) -> u32 {
+use std::io::BufRead;
+
+use crate::parallel;
+
+mod explain;
+mod vocabulary;
+
+pub use explain::{explain, explain_bytes};
+use vocabulary::Scanner;
+pub use vocabulary::{Combine, Vocabulary};
+
+fn char_to_num(char: Option<u8>) -> u64 {
     match char {
-        Some(c) => (c - b'0') as u32,
+        Some(c) => (c - b'0') as u64,
         None => 0,
     }
 }
 
-pub fn part1(input_path: &str) -> String {
-    let input = std::fs::read(input_path).unwrap();
-    let mut total = 0;
-    let mut first: Option<u8> = None;
-    let mut last: Option<u8> = None;
-    for char in input {
-        match char {
-            b'0'..=b'9' => {
-                if first.is_none() {
-                    first = Some(char);
+/// Running part1 total, fed the input a chunk at a time. Lines may span
+/// chunks.
+#[derive(Default)]
+struct Part1Total {
+    total: u64,
+    first: Option<u8>,
+    last: Option<u8>,
+}
+
+impl Part1Total {
+    fn feed(&mut self, input: &[u8]) {
+        for &char in input {
+            match char {
+                b'0'..=b'9' => {
+                    if self.first.is_none() {
+                        self.first = Some(char);
+                    }
+                    self.last = Some(char);
                 }
-                last = Some(char);
-            }
-            b'\n' => {
-                total += 10 * char_to_num(first) + char_to_num(last);
-                first = None;
-                last = None;
+                b'\n' => {
+                    self.total += 10 * char_to_num(self.first) + char_to_num(self.last);
+                    self.first = None;
+                    self.last = None;
+                }
+                _ => continue,
             }
-            _ => continue,
         }
     }
-    total += 10 * char_to_num(first) + char_to_num(last);
-    total.to_string()
-}
-
-pub fn part2(input_path: &str) -> String {
-    let input = std::fs::read(input_path).unwrap();
-    let mut total: u32 = 0;
-    let mut first: Option<u32> = None;
-    let mut last: Option<u32> = None;
-
-    let words_of_interest: Vec<&[u8]> = vec![
-        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-    ]
-    .iter()
-    .map(|word| word.as_bytes())
-    .collect();
-
-    for (pos, char) in input.iter().enumerate() {
-        match char {
-            b'0'..=b'9' => {
-                if first.is_none() {
-                    first = Some((char - b'0').into());
-                }
-                last = Some((char - b'0').into());
-            }
-            b'\n' => {
-                total += 10 * first.unwrap_or_default() + last.unwrap_or_default();
-                first = None;
-                last = None;
-            }
-            _ => {
-                for (idx, word) in words_of_interest.iter().enumerate() {
-                    if input[pos..].starts_with(word) {
-                        if first.is_none() {
-                            first = Some(idx as u32 + 1);
-                        }
-                        last = Some(idx as u32 + 1);
-                        break;
-                    }
-                }
-            }
+
+    fn finish(self) -> u64 {
+        self.total + 10 * char_to_num(self.first) + char_to_num(self.last)
+    }
+}
+
+pub fn part1(input_path: &str) -> Result<u64, String> {
+    let input = std::fs::read(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part1_bytes(&input)
+}
+
+pub fn part1_bytes(input: &[u8]) -> Result<u64, String> {
+    let mut total = Part1Total::default();
+    total.feed(input);
+    Ok(total.finish())
+}
+
+/// Same as `part1_bytes`, with the lines split across `threads` threads.
+pub fn part1_parallel(input: &[u8], threads: usize) -> Result<u64, String> {
+    let pieces = parallel::line_ranges(input, threads);
+    let totals = parallel::map_chunks(&pieces, threads, |pieces| {
+        pieces
+            .iter()
+            .map(|(_, range)| {
+                let mut total = Part1Total::default();
+                total.feed(&input[range.clone()]);
+                total.finish()
+            })
+            .sum::<u64>()
+    });
+    Ok(totals.iter().sum())
+}
+
+/// Same as `part1`, reading through `reader` a buffer at a time.
+pub fn part1_reader(mut reader: impl BufRead) -> Result<u64, String> {
+    let mut total = Part1Total::default();
+    loop {
+        let chunk = reader
+            .fill_buf()
+            .map_err(|e| format!("File read error: {}", e))?;
+        if chunk.is_empty() {
+            return Ok(total.finish());
+        }
+        total.feed(chunk);
+        let len = chunk.len();
+        reader.consume(len);
+    }
+}
+
+pub fn part2(input_path: &str) -> Result<u64, String> {
+    calibrate(input_path, &Vocabulary::english(), Combine::Concat)
+}
+
+pub fn part2_bytes(input: &[u8]) -> Result<u64, String> {
+    calibrate_bytes(input, &Vocabulary::english(), Combine::Concat)
+}
+
+pub fn part2_parallel(input: &[u8], threads: usize) -> Result<u64, String> {
+    calibrate_parallel(input, &Vocabulary::english(), Combine::Concat, threads)
+}
+
+pub fn part2_reader(reader: impl BufRead) -> Result<u64, String> {
+    calibrate_reader(reader, &Vocabulary::english(), Combine::Concat)
+}
+
+/// Running calibration total, fed one line at a time.
+struct Calibration {
+    scanner: Scanner,
+    combine: Combine,
+    total: u64,
+}
+
+impl Calibration {
+    fn new(vocabulary: &Vocabulary, combine: Combine) -> Calibration {
+        Calibration {
+            scanner: vocabulary.scanner(),
+            combine,
+            total: 0,
         }
     }
 
-    total += 10 * first.unwrap_or_default() + last.unwrap_or_default();
-    total.to_string()
+    fn add_line(&mut self, line_idx: usize, line: &[u8]) -> Result<(), String> {
+        if let Some((first, last)) = self.scanner.first_and_last(line) {
+            self.total = self
+                .combine
+                .apply(self.scanner.value(&first), self.scanner.value(&last))
+                .and_then(|val| self.total.checked_add(val))
+                .ok_or(format!("Overflow at line {}", line_idx + 1))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sums the calibration value of every line, taking the first and last
+/// digit or `vocabulary` word on the line and joining them with `combine`.
+pub fn calibrate(
+    input_path: &str,
+    vocabulary: &Vocabulary,
+    combine: Combine,
+) -> Result<u64, String> {
+    let input = std::fs::read(input_path).map_err(|e| format!("File read error: {}", e))?;
+    calibrate_bytes(&input, vocabulary, combine)
+}
+
+pub fn calibrate_bytes(
+    input: &[u8],
+    vocabulary: &Vocabulary,
+    combine: Combine,
+) -> Result<u64, String> {
+    let mut calibration = Calibration::new(vocabulary, combine);
+    for (line_idx, line) in input.split(|c| *c == b'\n').enumerate() {
+        calibration.add_line(line_idx, line)?;
+    }
+    Ok(calibration.total)
+}
+
+/// Same as `calibrate_bytes`, with the lines split across `threads` threads.
+pub fn calibrate_parallel(
+    input: &[u8],
+    vocabulary: &Vocabulary,
+    combine: Combine,
+    threads: usize,
+) -> Result<u64, String> {
+    let pieces = parallel::line_ranges(input, threads);
+    let totals: Vec<Result<u64, String>> = parallel::map_chunks(&pieces, threads, |pieces| {
+        let mut calibration = Calibration::new(vocabulary, combine);
+        for (first_line, range) in pieces.iter() {
+            for (line_idx, line) in input[range.clone()].split(|c| *c == b'\n').enumerate() {
+                calibration.add_line(first_line + line_idx, line)?;
+            }
+        }
+        Ok(calibration.total)
+    });
+    totals.into_iter().try_fold(0u64, |sum, total| {
+        sum.checked_add(total?)
+            .ok_or("Overflow adding up the lines".to_string())
+    })
+}
+
+/// Same as `calibrate`, reading through `reader` a line at a time.
+pub fn calibrate_reader(
+    mut reader: impl BufRead,
+    vocabulary: &Vocabulary,
+    combine: Combine,
+) -> Result<u64, String> {
+    let mut calibration = Calibration::new(vocabulary, combine);
+    let mut line = Vec::new();
+    for line_idx in 0.. {
+        line.clear();
+        let len = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| format!("File read error: {}", e))?;
+        if len == 0 {
+            break;
+        }
+        calibration.add_line(line_idx, line.strip_suffix(b"\n").unwrap_or(&line))?;
+    }
+    Ok(calibration.total)
 }
@@ -0,0 +1,248 @@
+use std::{fs, str::FromStr};
+
+use crate::aho_corasick::{AhoCorasick, Match};
+
+/// Words that stand for a value in a calibration line, in addition to the
+/// plain digits 0-9.
+#[derive(Debug, Clone)]
+pub struct Vocabulary {
+    entries: Vec<(String, u64)>,
+    matching: Matching,
+}
+
+/// How words that overlap on a line are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matching {
+    /// Every occurrence counts, even inside another word, so "eightwo" is 8
+    /// then 2
+    Overlapping,
+    /// The line is read left to right taking the longest word at each
+    /// position, so "VIII" is a single 8 rather than 8 then 1
+    Longest,
+}
+
+const ENGLISH: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const FRENCH: [&str; 9] = [
+    "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+const GERMAN: [&str; 9] = [
+    "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+const SPANISH: [&str; 9] = [
+    "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+const ROMAN: [&str; 10] = ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X"];
+
+impl Vocabulary {
+    fn from_words(words: &[&str], matching: Matching) -> Vocabulary {
+        Vocabulary {
+            entries: words
+                .iter()
+                .zip(1..)
+                .map(|(word, val)| (word.to_string(), val))
+                .collect(),
+            matching,
+        }
+    }
+
+    pub fn english() -> Vocabulary {
+        Vocabulary::from_words(&ENGLISH, Matching::Overlapping)
+    }
+
+    /// No words at all, only the digits, as in part1.
+    pub fn digits() -> Vocabulary {
+        Vocabulary {
+            entries: Vec::new(),
+            matching: Matching::Overlapping,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Vocabulary> {
+        match name {
+            "digits" => Some(Vocabulary::digits()),
+            "english" => Some(Vocabulary::english()),
+            "french" => Some(Vocabulary::from_words(&FRENCH, Matching::Overlapping)),
+            "german" => Some(Vocabulary::from_words(&GERMAN, Matching::Overlapping)),
+            "spanish" => Some(Vocabulary::from_words(&SPANISH, Matching::Overlapping)),
+            "roman" => Some(Vocabulary::from_words(&ROMAN, Matching::Longest)),
+            _ => None,
+        }
+    }
+
+    /// Reads a vocabulary file, see the `FromStr` impl for the format.
+    pub fn from_file(path: &str) -> Result<Vocabulary, String> {
+        fs::read_to_string(path)
+            .map_err(|e| format!("File read error: {}", e))?
+            .parse()
+    }
+
+    /// A built-in vocabulary name, or else a path to a vocabulary file.
+    pub fn load(spec: &str) -> Result<Vocabulary, String> {
+        match Vocabulary::builtin(spec) {
+            Some(vocabulary) => Ok(vocabulary),
+            None => Vocabulary::from_file(spec),
+        }
+    }
+
+    /// A scanner for the digits and every word.
+    pub(super) fn scanner(&self) -> Scanner {
+        let mut patterns: Vec<(String, u64)> = (0..=9).map(|d| (d.to_string(), d)).collect();
+        patterns.extend(self.entries.iter().cloned());
+        let automaton = AhoCorasick::new(&patterns.iter().map(|(p, _)| p).collect::<Vec<_>>());
+        Scanner {
+            values: patterns.into_iter().map(|(_, value)| value).collect(),
+            automaton,
+            matching: self.matching,
+        }
+    }
+}
+
+/// A vocabulary compiled for finding values on a line.
+pub(super) struct Scanner {
+    values: Vec<u64>,
+    automaton: AhoCorasick,
+    matching: Matching,
+}
+
+impl Scanner {
+    /// The first and last words or digits on `line`, which are the same one
+    /// when there is only one.
+    pub(super) fn first_and_last(&self, line: &[u8]) -> Option<(Match, Match)> {
+        match self.matching {
+            Matching::Overlapping => self.automaton.first_and_last(line),
+            Matching::Longest => {
+                let found = self.automaton.find_longest(line);
+                Some((*found.first()?, *found.last()?))
+            }
+        }
+    }
+
+    pub(super) fn value(&self, m: &Match) -> u64 {
+        self.values[m.pattern]
+    }
+}
+
+impl FromStr for Vocabulary {
+    type Err = String;
+
+    /// One `word=value` pair per line. Blank lines and lines starting with
+    /// `#` are skipped. Words are matched overlapping, like the spelled out
+    /// built-ins.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (line_idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (word, value) = line
+                .split_once('=')
+                .ok_or(format!("Line {}: expected word=value", line_idx + 1))?;
+            let word = word.trim();
+            if word.is_empty() {
+                return Err(format!("Line {}: empty word", line_idx + 1));
+            }
+            let value = value
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| format!("Line {}: bad value: {}", line_idx + 1, e))?;
+            entries.push((word.to_string(), value));
+        }
+        Ok(Vocabulary {
+            entries,
+            matching: Matching::Overlapping,
+        })
+    }
+}
+
+/// How the first and last values of a line make its calibration value.
+#[derive(Debug, Clone, Copy)]
+pub enum Combine {
+    /// Decimal concatenation, 10 * first + last for single digits
+    Concat,
+    Sum,
+    Product,
+}
+
+impl Combine {
+    pub(super) fn apply(&self, first: u64, last: u64) -> Option<u64> {
+        match self {
+            Combine::Concat => {
+                let shift = 10u64.checked_pow(last.checked_ilog10().unwrap_or(0) + 1)?;
+                first.checked_mul(shift)?.checked_add(last)
+            }
+            Combine::Sum => first.checked_add(last),
+            Combine::Product => first.checked_mul(last),
+        }
+    }
+}
+
+impl FromStr for Combine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "concat" => Ok(Combine::Concat),
+            "sum" => Ok(Combine::Sum),
+            "product" => Ok(Combine::Product),
+            _ => Err(format!("Unknown combine rule: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_and_last(vocabulary: &Vocabulary, line: &str) -> Option<(u64, u64)> {
+        let scanner = vocabulary.scanner();
+        scanner
+            .first_and_last(line.as_bytes())
+            .map(|(first, last)| (scanner.value(&first), scanner.value(&last)))
+    }
+
+    #[test]
+    fn spelled_words_overlap() {
+        let english = Vocabulary::english();
+        assert_eq!(first_and_last(&english, "eightwo"), Some((8, 2)));
+        assert_eq!(first_and_last(&english, "xtwone3four"), Some((2, 4)));
+        assert_eq!(first_and_last(&english, "oneight"), Some((1, 8)));
+        assert_eq!(first_and_last(&english, "zoneight234"), Some((1, 4)));
+        assert_eq!(first_and_last(&Vocabulary::digits(), "eightwo"), None);
+        let german = Vocabulary::builtin("german").unwrap();
+        assert_eq!(first_and_last(&german, "fünfzwei"), Some((5, 2)));
+    }
+
+    #[test]
+    fn roman_numerals_take_the_longest() {
+        let roman = Vocabulary::builtin("roman").unwrap();
+        assert_eq!(first_and_last(&roman, "VIII"), Some((8, 8)));
+        assert_eq!(first_and_last(&roman, "aXIVb"), Some((10, 4)));
+        assert_eq!(first_and_last(&roman, "IIII"), Some((3, 1)));
+        assert_eq!(first_and_last(&roman, "VI2"), Some((6, 2)));
+        assert_eq!(first_and_last(&roman, "vii"), None);
+    }
+
+    #[test]
+    fn vocabulary_files() {
+        let path = std::env::temp_dir().join(format!("vocabulary_{}.txt", std::process::id()));
+        let load = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            Vocabulary::load(path.to_str().unwrap())
+        };
+        let vocabulary = load("# dozens\n\ndozen = 12\nscore=20\n").unwrap();
+        assert_eq!(first_and_last(&vocabulary, "dozenscore"), Some((12, 20)));
+        assert_eq!(
+            load("dozen 12\n").unwrap_err(),
+            "Line 1: expected word=value"
+        );
+        assert_eq!(load("ok=1\n=2\n").unwrap_err(), "Line 2: empty word");
+        assert!(load("dozen=twelve\n")
+            .unwrap_err()
+            .starts_with("Line 1: bad value"));
+        fs::remove_file(&path).unwrap();
+        assert!(Vocabulary::load("no_such_vocabulary").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fs, str::FromStr};
+
+/// The cubes in the bag, as (color, count) in the order they were given.
+#[derive(Debug, Clone)]
+pub struct Bag {
+    cubes: Vec<(String, u32)>,
+}
+
+impl Default for Bag {
+    /// The bag from the puzzle text.
+    fn default() -> Self {
+        Bag {
+            cubes: vec![
+                ("red".to_string(), 12),
+                ("green".to_string(), 13),
+                ("blue".to_string(), 14),
+            ],
+        }
+    }
+}
+
+impl Bag {
+    /// Cubes of `color` in the bag, 0 for colors it does not hold.
+    pub fn count(&self, color: &str) -> u32 {
+        self.cubes
+            .iter()
+            .find(|(c, _)| c == color)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.cubes.iter().map(|(color, _)| color.as_str())
+    }
+
+    /// Either an inline `color=count,...` list, or a path to a file holding
+    /// one, where newlines may stand in for the commas.
+    pub fn load(spec: &str) -> Result<Bag, String> {
+        if spec.contains('=') {
+            spec.parse()
+        } else {
+            fs::read_to_string(spec)
+                .map_err(|e| format!("File read error: {}", e))?
+                .parse()
+        }
+    }
+}
+
+impl FromStr for Bag {
+    type Err = String;
+
+    /// `#` starts a comment that runs to the end of the line.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cubes: Vec<(String, u32)> = Vec::new();
+        let entries = s
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty());
+        for entry in entries {
+            let (color, count) = entry
+                .split_once('=')
+                .ok_or(format!("Bad bag entry {:?}, expected color=count", entry))?;
+            let color = color.trim();
+            if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(format!("Bad bag color {:?}", color));
+            }
+            if cubes.iter().any(|(c, _)| c == color) {
+                return Err(format!("Color {} given twice", color));
+            }
+            let count = count
+                .trim()
+                .parse::<u32>()
+                .map_err(|e| format!("Bad count for {}: {}", color, e))?;
+            cubes.push((color.to_string(), count));
+        }
+        Ok(Bag { cubes })
+    }
+}
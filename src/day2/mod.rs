@@ -1,86 +1,332 @@
-use core::panic;
-
-pub fn part1(input_path: &str) -> String {
-    let input = std::fs::read_to_string(input_path).unwrap();
-
-    const RED_MAX: u32 = 12;
-    const GREEN_MAX: u32 = 13;
-    const BLUE_MAX: u32 = 14;
-
-    let mut total = 0;
-    for line in input.lines() {
-        let trim_game = line.split_at(5).1;
-
-        let colon_idx = trim_game.find(":").unwrap();
-
-        let split = trim_game.split_at(colon_idx);
-        let game_id: u32 = split.0.parse().unwrap();
-
-        let mut passed = true;
-        for cube_set in split.1[2..].split("; ") {
-            for cube_count in cube_set.split(", ") {
-                let cube_split = cube_count.split_once(" ").unwrap();
-                let count = cube_split.0.parse::<u32>().unwrap();
-                let color = cube_split.1;
-
-                match color {
-                    "red" => {
-                        passed &= count <= RED_MAX;
-                    }
-                    "green" => {
-                        passed &= count <= GREEN_MAX;
-                    }
-                    "blue" => {
-                        passed &= count <= BLUE_MAX;
-                    }
-                    _ => panic!("Unknown color: {}", color),
-                }
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alpha1, space0, space1, u32},
+    combinator::{all_consuming, cut},
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    Finish, IResult,
+};
+use std::{collections::HashMap, fs, io::BufRead};
+
+use crate::parallel;
+
+mod bag;
+mod report;
+
+pub use bag::Bag;
+pub use report::{report, report_str, ruled_out_by_color};
+
+#[derive(Debug)]
+struct CubeCount<'a> {
+    count: u32,
+    color: &'a str,
+}
+
+#[derive(Debug)]
+struct Draw<'a> {
+    cubes: Vec<CubeCount<'a>>,
+}
+
+#[derive(Debug)]
+struct Game<'a> {
+    id: u32,
+    draws: Vec<Draw<'a>>,
+}
+
+fn parse_cube_count(input: &str) -> IResult<&str, CubeCount<'_>> {
+    let (remain, (count, _, color)) = tuple((u32, space1, alpha1))(input)?;
+    Ok((remain, CubeCount { count, color }))
+}
+
+fn parse_draw(input: &str) -> IResult<&str, Draw<'_>> {
+    // cut so a malformed count is reported where it is, rather than where
+    // the list stopped
+    let (remain, cubes) =
+        separated_list1(delimited(space0, tag(","), space0), cut(parse_cube_count))(input)?;
+    Ok((remain, Draw { cubes }))
+}
+
+fn parse_game(input: &str) -> IResult<&str, Game<'_>> {
+    let (remain, (_, _, _, id, _, _, _)) =
+        tuple((space0, tag("Game"), space1, u32, space0, tag(":"), space0))(input)?;
+    let (remain, draws) = separated_list1(delimited(space0, tag(";"), space0), parse_draw)(remain)?;
+    let (remain, _) = space0(remain)?;
+    Ok((remain, Game { id, draws }))
+}
+
+/// Parses one game per line, skipping blank lines. Errors carry the 1-based
+/// line and column where parsing failed.
+fn parse(input: &str) -> Result<Vec<Game<'_>>, String> {
+    let mut games = Vec::new();
+    for (line_idx, line) in input.lines().enumerate() {
+        games.extend(parse_line(line_idx, line)?);
+    }
+    Ok(games)
+}
+
+/// The game on one line, or None if the line is blank.
+fn parse_line(line_idx: usize, line: &str) -> Result<Option<Game<'_>>, String> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let (_, game) = all_consuming(parse_game)(line).finish().map_err(|e| {
+        format!(
+            "Parse Error at line {} column {}: {:?} near {:?}",
+            line_idx + 1,
+            line.len() - e.input.len() + 1,
+            e.code,
+            e.input
+        )
+    })?;
+    Ok(Some(game))
+}
+
+/// Calls `f` on the game on each line read from `reader`, one at a time.
+fn for_each_game(
+    reader: impl BufRead,
+    mut f: impl FnMut(&Game) -> Result<(), String>,
+) -> Result<(), String> {
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("File read error: {}", e))?;
+        if let Some(game) = parse_line(line_idx, &line)? {
+            f(&game)?;
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Game<'a> {
+    fn possible(&self, bag: &Bag) -> bool {
+        self.draws
+            .iter()
+            .flat_map(|draw| draw.cubes.iter())
+            .all(|cube| cube.count <= bag.count(cube.color))
+    }
+
+    /// Fewest cubes of each color that make the game possible: `colors`
+    /// first, at 0 if the game never draws them, then any other colors the
+    /// game draws in the order they first appear.
+    fn min_bag(&self, colors: &[&'a str]) -> Vec<(&'a str, u32)> {
+        let mut min_bag: Vec<(&str, u32)> = colors.iter().map(|color| (*color, 0)).collect();
+        for cube in self.draws.iter().flat_map(|draw| draw.cubes.iter()) {
+            match min_bag.iter_mut().find(|(color, _)| *color == cube.color) {
+                Some((_, count)) => *count = (*count).max(cube.count),
+                None => min_bag.push((cube.color, cube.count)),
+            }
+        }
+        min_bag
+    }
+
+    fn power(&self) -> Option<u64> {
+        self.min_bag(&[])
+            .iter()
+            .try_fold(1u64, |power, (_, count)| power.checked_mul(*count as u64))
+    }
+}
+
+/// Every color drawn in `games`, in the order they first appear.
+fn colors<'a>(games: &[Game<'a>]) -> Vec<&'a str> {
+    let mut colors: Vec<&str> = Vec::new();
+    for game in games.iter() {
+        for (color, _) in game.min_bag(&colors) {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    colors
+}
+
+fn add_id(total: u32, game: &Game) -> Result<u32, String> {
+    total
+        .checked_add(game.id)
+        .ok_or(format!("Id sum overflowed at game {}", game.id))
+}
+
+/// Running part2 total. A color a game never draws counts as 0, so a game
+/// only adds to the total if it draws every color seen anywhere in the input.
+/// That is not known until the end, so powers are summed by how many colors
+/// the game draws, with None once a sum overflows.
+#[derive(Default)]
+struct PowerTotal {
+    colors: Vec<String>,
+    by_colors_drawn: HashMap<usize, Option<u64>>,
+}
+
+impl PowerTotal {
+    fn add(&mut self, game: &Game) {
+        let drawn = game.min_bag(&[]);
+        for (color, _) in drawn.iter() {
+            if !self.colors.iter().any(|c| c == color) {
+                self.colors.push(color.to_string());
             }
         }
-        if passed {
-            total += game_id;
+        let sum = self.by_colors_drawn.entry(drawn.len()).or_insert(Some(0));
+        *sum = sum
+            .zip(game.power())
+            .and_then(|(sum, power)| sum.checked_add(power));
+    }
+
+    fn merge(&mut self, other: PowerTotal) {
+        for color in other.colors {
+            if !self.colors.contains(&color) {
+                self.colors.push(color);
+            }
+        }
+        for (drawn, other_sum) in other.by_colors_drawn {
+            let sum = self.by_colors_drawn.entry(drawn).or_insert(Some(0));
+            *sum = sum
+                .zip(other_sum)
+                .and_then(|(sum, other_sum)| sum.checked_add(other_sum));
         }
     }
-    total.to_string()
-}
-
-pub fn part2(input_path: &str) -> String {
-    let input = std::fs::read_to_string(input_path).unwrap();
-
-    let mut total = 0;
-    // For each game
-    for line in input.lines() {
-        // Trim "Game "
-        let trim_game = line.split_at(5).1;
-        let colon_idx = trim_game.find(":").unwrap();
-        let split = trim_game.split_at(colon_idx);
-
-        let mut red_max = 0;
-        let mut green_max = 0;
-        let mut blue_max = 0;
-        // For each set of cubes
-        for cube_set in split.1[2..].split("; ") {
-            // For each count of a single cube
-            for cube_count in cube_set.split(", ") {
-                let cube_split = cube_count.split_once(" ").unwrap();
-                let count = cube_split.0.parse::<u32>().unwrap();
-                let color = cube_split.1;
-
-                match color {
-                    "red" => {
-                        red_max = red_max.max(count);
-                    }
-                    "green" => {
-                        green_max = green_max.max(count);
-                    }
-                    "blue" => {
-                        blue_max = blue_max.max(count);
-                    }
-                    _ => panic!("Unknown color: {}", color),
-                }
+
+    fn finish(self) -> Result<u64, String> {
+        match self.by_colors_drawn.get(&self.colors.len()) {
+            Some(sum) => sum.ok_or("Power sum overflowed".to_string()),
+            None => Ok(0),
+        }
+    }
+}
+
+pub fn part1(input_path: &str, bag: &Bag) -> Result<u32, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part1_str(&input, bag)
+}
+
+pub fn part1_str(input: &str, bag: &Bag) -> Result<u32, String> {
+    let games = parse(input)?;
+
+    let mut total: u32 = 0;
+    for game in games.iter().filter(|game| game.possible(bag)) {
+        total = add_id(total, game)?;
+    }
+    Ok(total)
+}
+
+/// Runs `f` on the games in each piece of `input` on `threads` threads,
+/// giving back each piece's result in order.
+fn map_pieces<R: Send>(
+    input: &str,
+    threads: usize,
+    f: impl Fn(&[Game]) -> Result<R, String> + Sync,
+) -> Vec<Result<R, String>> {
+    let pieces = parallel::line_ranges(input.as_bytes(), threads);
+    parallel::map_chunks(&pieces, threads, |pieces| {
+        let mut games = Vec::new();
+        for (first_line, range) in pieces.iter() {
+            // Pieces end after a newline, so they are valid str ranges
+            for (line_idx, line) in input[range.clone()].lines().enumerate() {
+                games.extend(parse_line(first_line + line_idx, line)?);
             }
         }
-        total += red_max * green_max * blue_max;
+        f(&games)
+    })
+}
+
+/// Same as `part1_str`, with the games split across `threads` threads.
+pub fn part1_parallel(input: &str, bag: &Bag, threads: usize) -> Result<u32, String> {
+    let totals = map_pieces(input, threads, |games| {
+        let mut total: u32 = 0;
+        for game in games.iter().filter(|game| game.possible(bag)) {
+            total = add_id(total, game)?;
+        }
+        Ok(total)
+    });
+    totals.into_iter().try_fold(0u32, |sum, total| {
+        sum.checked_add(total?)
+            .ok_or("Id sum overflowed".to_string())
+    })
+}
+
+/// Same as `part1`, reading through `reader` a game at a time.
+pub fn part1_reader(reader: impl BufRead, bag: &Bag) -> Result<u32, String> {
+    let mut total: u32 = 0;
+    for_each_game(reader, |game| {
+        if game.possible(bag) {
+            total = add_id(total, game)?;
+        }
+        Ok(())
+    })?;
+    Ok(total)
+}
+
+/// Sums the power, the product of every color's count, of each game's
+/// minimum bag. The bag holds every color drawn anywhere in the input, at 0
+/// for the colors a game never draws.
+pub fn part2(input_path: &str) -> Result<u64, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part2_str(&input)
+}
+
+pub fn part2_str(input: &str) -> Result<u64, String> {
+    let games = parse(input)?;
+
+    let mut total = PowerTotal::default();
+    for game in games.iter() {
+        total.add(game);
+    }
+    total.finish()
+}
+
+/// Same as `part2_str`, with the games split across `threads` threads.
+pub fn part2_parallel(input: &str, threads: usize) -> Result<u64, String> {
+    let totals = map_pieces(input, threads, |games| {
+        let mut total = PowerTotal::default();
+        for game in games.iter() {
+            total.add(game);
+        }
+        Ok(total)
+    });
+    let mut sum = PowerTotal::default();
+    for total in totals {
+        sum.merge(total?);
+    }
+    sum.finish()
+}
+
+/// Same as `part2`, reading through `reader` a game at a time.
+pub fn part2_reader(reader: impl BufRead) -> Result<u64, String> {
+    let mut total = PowerTotal::default();
+    for_each_game(reader, |game| {
+        total.add(game);
+        Ok(())
+    })?;
+    total.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_never_drawn_count_as_zero() {
+        let sample = fs::read_to_string("src/day2/part2_sample_input.txt").unwrap();
+        let bag: Bag = "red=12,green=13,blue=14,yellow=5".parse().unwrap();
+        assert_eq!(part1_str(&sample, &bag), Ok(8));
+        assert_eq!(part2_str(&sample), Ok(2286));
+        let reports = report_str(&sample, &bag).unwrap();
+        assert_eq!(reports.iter().map(|r| r.power()).sum::<u64>(), 2286);
+        assert_eq!(
+            reports[0].min_bag,
+            vec![
+                ("blue".to_string(), 6),
+                ("red".to_string(), 4),
+                ("green".to_string(), 2)
+            ]
+        );
+
+        let input = "Game 1: 2 yellow; 3 yellow, 1 red\nGame 2: 4 red\nGame 3: 1 red, 2 yellow\n";
+        assert_eq!(part2_str(input), Ok(5));
+        assert_eq!(part2_reader(input.as_bytes()), Ok(5));
+        for threads in 1..=3 {
+            assert_eq!(part2_parallel(input, threads), Ok(5));
+        }
+        let reports = report_str(input, &bag).unwrap();
+        assert_eq!(
+            reports[1].min_bag,
+            vec![("yellow".to_string(), 0), ("red".to_string(), 4)]
+        );
+        assert_eq!(reports.iter().map(|r| r.power()).sum::<u64>(), 5);
     }
-    total.to_string()
 }
@@ -0,0 +1,123 @@
+use std::{fmt::Display, fs};
+
+use super::{colors, parse, Bag};
+
+/// A color in a draw that the bag cannot supply.
+#[derive(Debug)]
+pub struct Violation {
+    // 1-based index of the draw within the game
+    pub draw: usize,
+    pub color: String,
+    pub count: u32,
+    pub limit: u32,
+}
+
+#[derive(Debug)]
+pub struct GameReport {
+    pub id: u32,
+    pub violations: Vec<Violation>,
+    pub min_bag: Vec<(String, u32)>,
+}
+
+impl GameReport {
+    pub fn possible(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    pub fn power(&self) -> u64 {
+        self.min_bag
+            .iter()
+            .map(|(_, count)| *count as u64)
+            .product()
+    }
+}
+
+impl Display for GameReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let min_bag: Vec<String> = self
+            .min_bag
+            .iter()
+            .map(|(color, count)| format!("{}={}", color, count))
+            .collect();
+        write!(
+            f,
+            "Game {}: {}, min bag {} power {}",
+            self.id,
+            if self.possible() {
+                "possible"
+            } else {
+                "impossible"
+            },
+            min_bag.join(","),
+            self.power()
+        )?;
+        for violation in self.violations.iter() {
+            write!(
+                f,
+                "\n    draw {}: {} {} > {}",
+                violation.draw, violation.count, violation.color, violation.limit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn report(input_path: &str, bag: &Bag) -> Result<Vec<GameReport>, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    report_str(&input, bag)
+}
+
+pub fn report_str(input: &str, bag: &Bag) -> Result<Vec<GameReport>, String> {
+    let games = parse(input)?;
+    let colors = colors(&games);
+
+    Ok(games
+        .iter()
+        .map(|game| {
+            let violations = game
+                .draws
+                .iter()
+                .enumerate()
+                .flat_map(|(draw_idx, draw)| {
+                    draw.cubes
+                        .iter()
+                        .filter(|cube| cube.count > bag.count(cube.color))
+                        .map(move |cube| Violation {
+                            draw: draw_idx + 1,
+                            color: cube.color.to_string(),
+                            count: cube.count,
+                            limit: bag.count(cube.color),
+                        })
+                })
+                .collect();
+            GameReport {
+                id: game.id,
+                violations,
+                min_bag: game
+                    .min_bag(&colors)
+                    .into_iter()
+                    .map(|(color, count)| (color.to_string(), count))
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// For every color, the number of games with a draw needing more of it than
+/// the bag holds. Bag colors come first, in order, then any others seen.
+pub fn ruled_out_by_color(reports: &[GameReport], bag: &Bag) -> Vec<(String, usize)> {
+    let mut ruled_out: Vec<(String, usize)> =
+        bag.colors().map(|color| (color.to_string(), 0)).collect();
+    for report in reports.iter() {
+        let mut colors: Vec<&str> = report.violations.iter().map(|v| v.color.as_str()).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        for color in colors {
+            match ruled_out.iter_mut().find(|(c, _)| c == color) {
+                Some((_, count)) => *count += 1,
+                None => ruled_out.push((color.to_string(), 1)),
+            }
+        }
+    }
+    ruled_out
+}
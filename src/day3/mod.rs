@@ -1,3 +1,15 @@
+use std::collections::HashMap;
+
+use crate::grid::Grid;
+
+mod render;
+mod repl;
+mod rules;
+
+pub use render::render;
+pub use repl::Session;
+pub use rules::Rules;
+
 #[derive(Debug)]
 struct MatchedNum {
     num: u32,
@@ -11,139 +23,217 @@ struct Gear {
     y_pos: usize,
 }
 
-fn parse_nums(
-    input: &Vec<u8>,
-    nums: &mut Vec<MatchedNum>,
-    gears: &mut Vec<Gear>,
-    line_len: &mut usize,
-    y_end: &mut usize,
-) {
-    let mut in_progress_num: Vec<u8> = Vec::new();
-    let mut x_pos = 0;
-    let mut y_pos = 0;
-    let mut saw_newline = false;
-
-    for char in input.iter() {
-        if saw_newline {
-            x_pos = 0;
-            y_pos += 1;
-            saw_newline = false;
-        }
-
-        match char {
-            b'0'..=b'9' => {
-                in_progress_num.push(*char);
-            }
-            c => {
-                if *c == b'\n' {
-                    saw_newline = true;
-                    *line_len = x_pos;
-                } else if *c == b'*' {
-                    gears.push(Gear { x_pos, y_pos });
+fn parse_nums(grid: &Grid<u8>, rules: &Rules) -> Result<(Vec<MatchedNum>, Vec<Gear>), String> {
+    let mut nums = Vec::new();
+    let mut gears = Vec::new();
+
+    for (y_pos, row) in grid.rows().enumerate() {
+        let mut in_progress_num: Vec<u8> = Vec::new();
+        // Chaining a '.' flushes a number that ends the row. It is past the
+        // grid, so never a gear even when '.' is one
+        for (x_pos, char) in row.iter().chain(std::iter::once(&b'.')).enumerate() {
+            match char {
+                b'0'..=b'9' => {
+                    in_progress_num.push(*char);
                 }
-                if !in_progress_num.is_empty() {
-                    let val = String::from_utf8_lossy(in_progress_num.as_slice())
-                        .parse::<u32>()
-                        .unwrap();
-                    nums.push(MatchedNum {
-                        num: val,
-                        x_start: x_pos - in_progress_num.len(),
-                        y_start: y_pos,
-                        len: in_progress_num.len(),
-                    });
-                    in_progress_num.clear();
+                c => {
+                    if x_pos < row.len() && rules.is_gear(*c) {
+                        gears.push(Gear { x_pos, y_pos });
+                    }
+                    if !in_progress_num.is_empty() {
+                        let x_start = x_pos - in_progress_num.len();
+                        let val = String::from_utf8_lossy(in_progress_num.as_slice())
+                            .parse::<u32>()
+                            .map_err(|e| {
+                                format!(
+                                    "Number at line {} column {}: {}",
+                                    y_pos + 1,
+                                    x_start + 1,
+                                    e
+                                )
+                            })?;
+                        nums.push(MatchedNum {
+                            num: val,
+                            x_start,
+                            y_start: y_pos,
+                            len: in_progress_num.len(),
+                        });
+                        in_progress_num.clear();
+                    }
                 }
             }
         }
-        x_pos += 1;
     }
-    *y_end = y_pos;
+    Ok((nums, gears))
 }
 
-pub fn part1(input_path: &str) -> String {
-    let input = std::fs::read(input_path).unwrap();
-    let mut nums: Vec<MatchedNum> = Vec::new();
-    let mut gears: Vec<Gear> = Vec::new();
-    let mut line_len = 0;
-    let mut y_end = 0;
-    parse_nums(&input, &mut nums, &mut gears, &mut line_len, &mut y_end);
-
-    let mut total = 0;
-    for num in nums.iter() {
-        let x_scan_start = if num.x_start == 0 { 0 } else { num.x_start - 1 };
-        let x_scan_end = if num.x_start + num.len == line_len {
-            line_len
-        } else {
-            num.x_start + num.len
-        };
-
-        let y_scan_start = if num.y_start == 0 { 0 } else { num.y_start - 1 };
-        let y_scan_end = if num.y_start == y_end {
-            y_end
-        } else {
-            num.y_start + 1
-        };
-
-        'outer: for y in y_scan_start..=y_scan_end {
-            for x in x_scan_start..=x_scan_end {
-                let to_check_idx = y * (line_len + 1) + x;
-                match input[to_check_idx] as char {
-                    '0'..='9' => continue,
-                    '.' => continue,
-                    '\n' => continue,
-                    _ => {
-                        total += num.num;
-                        break 'outer;
-                    }
-                }
-            }
+/// The id (index into the number list) of the number covering each cell.
+fn number_cells(grid: &Grid<u8>, nums: &[MatchedNum]) -> Grid<Option<usize>> {
+    let mut num_at: Grid<Option<usize>> = Grid::new(
+        grid.width(),
+        grid.height(),
+        vec![None; grid.width() * grid.height()],
+    )
+    .unwrap();
+    for (id, num) in nums.iter().enumerate() {
+        for x in num.x_start..num.x_start + num.len {
+            num_at[(x, num.y_start)] = Some(id);
         }
     }
+    num_at
+}
+
+/// Ids of the numbers touching each cell that passes `is_symbol`. Built from
+/// `number_cells`, so every symbol only checks its eight neighbours.
+fn adjacency(
+    grid: &Grid<u8>,
+    nums: &[MatchedNum],
+    is_symbol: impl Fn(u8) -> bool,
+) -> HashMap<(usize, usize), Vec<usize>> {
+    let num_at = number_cells(grid, nums);
+
+    let mut adjacency = HashMap::new();
+    for (x, y) in grid.positions().filter(|pos| is_symbol(grid[*pos])) {
+        let mut ids: Vec<usize> = grid
+            .neighbours8(x, y)
+            .filter_map(|pos| num_at[pos])
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        adjacency.insert((x, y), ids);
+    }
+    adjacency
+}
 
-    total.to_string()
+fn load(input_path: &str) -> Result<Grid<u8>, String> {
+    let input = std::fs::read(input_path).map_err(|e| format!("File read error: {}", e))?;
+    parse(&input)
 }
 
-pub fn part2(input_path: &str) -> String {
-    let input = std::fs::read(input_path).unwrap();
-    let mut nums: Vec<MatchedNum> = Vec::new();
-    let mut gears: Vec<Gear> = Vec::new();
-    let mut line_len = 0;
-    let mut y_end = 0;
-    parse_nums(&input, &mut nums, &mut gears, &mut line_len, &mut y_end);
-    let mut total = 0;
-
-    for gear in gears.iter() {
-        let mut nearby_nums = Vec::new();
-
-        for num in nums.iter() {
-            let x_scan_start = if num.x_start == 0 { 0 } else { num.x_start - 1 };
-            let x_scan_end = if num.x_start + num.len == line_len {
-                line_len
-            } else {
-                num.x_start + num.len
-            };
-
-            let y_scan_start = if num.y_start == 0 { 0 } else { num.y_start - 1 };
-            let y_scan_end = if num.y_start == y_end {
-                y_end
-            } else {
-                num.y_start + 1
-            };
-
-            'outer: for y in y_scan_start..=y_scan_end {
-                for x in x_scan_start..=x_scan_end {
-                    if x == gear.x_pos && y == gear.y_pos {
-                        nearby_nums.push(num);
-                        break 'outer;
-                    }
+/// Parses the schematic, accepting LF or CRLF line endings and any number of
+/// trailing newlines. Errors if the lines are not all the same length.
+fn parse(input: &[u8]) -> Result<Grid<u8>, String> {
+    let mut normalised = Vec::with_capacity(input.len());
+    for (idx, c) in input.iter().enumerate() {
+        if *c == b'\r' && input.get(idx + 1).is_none_or(|next| *next == b'\n') {
+            continue;
+        }
+        normalised.push(*c);
+    }
+    while normalised.last() == Some(&b'\n') {
+        normalised.pop();
+    }
+
+    Grid::parse(&normalised).map_err(|e| format!("Schematic is not rectangular: {}", e))
+}
+
+pub fn part1(input_path: &str) -> Result<u64, String> {
+    score(input_path, &Rules::part1())
+}
+
+pub fn part1_bytes(input: &[u8]) -> Result<u64, String> {
+    score_grid(&parse(input)?, &Rules::part1())
+}
+
+pub fn part2(input_path: &str) -> Result<u64, String> {
+    score(input_path, &Rules::part2())
+}
+
+pub fn part2_bytes(input: &[u8]) -> Result<u64, String> {
+    score_grid(&parse(input)?, &Rules::part2())
+}
+
+pub fn score(input_path: &str, rules: &Rules) -> Result<u64, String> {
+    score_grid(&load(input_path)?, rules)
+}
+
+pub fn score_bytes(input: &[u8], rules: &Rules) -> Result<u64, String> {
+    score_grid(&parse(input)?, rules)
+}
+
+fn score_grid(grid: &Grid<u8>, rules: &Rules) -> Result<u64, String> {
+    let (nums, gears) = parse_nums(grid, rules)?;
+
+    match &rules.gear {
+        None => {
+            let adjacency = adjacency(grid, &nums, |c| rules.is_symbol(c));
+            let mut is_part = vec![false; nums.len()];
+            for id in adjacency.values().flatten() {
+                is_part[*id] = true;
+            }
+            Ok(nums
+                .iter()
+                .zip(is_part)
+                .filter(|(_, is_part)| *is_part)
+                .map(|(num, _)| num.num as u64)
+                .sum::<u64>())
+        }
+        Some(gear_rule) => {
+            let adjacency = adjacency(grid, &nums, |c| rules.is_gear(c));
+            let mut total: u64 = 0;
+            for gear in gears.iter() {
+                let Some(nearby_nums) = adjacency.get(&(gear.x_pos, gear.y_pos)) else {
+                    continue;
+                };
+                if nearby_nums.len() != gear_rule.neighbours {
+                    continue;
                 }
+                total = gear_rule
+                    .value(nearby_nums.iter().map(|id| nums[*id].num as u64))
+                    .and_then(|value| total.checked_add(value))
+                    .ok_or(format!(
+                        "Gear total overflowed at line {} column {}",
+                        gear.y_pos + 1,
+                        gear.x_pos + 1
+                    ))?;
             }
+            Ok(total)
         }
+    }
+}
 
-        if nearby_nums.len() == 2 {
-            total += nearby_nums[0].num * nearby_nums[1].num;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dots_can_be_gears() {
+        let rules: Rules = "gears=.".parse().unwrap();
+        assert_eq!(score_grid(&parse(b"1.2\n...\n").unwrap(), &rules), Ok(4));
+        assert_eq!(score_grid(&parse(b"12\n..\n").unwrap(), &rules), Ok(0));
+        let sample = std::fs::read("src/day3/part1_sample_input.txt").unwrap();
+        assert!(score_grid(&parse(&sample).unwrap(), &rules).is_ok());
     }
 
-    total.to_string()
+    #[test]
+    fn line_endings_and_ragged_lines() {
+        // A CR is part of the line ending, not a symbol next to the 12
+        assert_eq!(part1_bytes(b"...\r\n.12\r\n"), Ok(0));
+        assert_eq!(part1_bytes(b"..*\r\n.12\r\n\r\n"), Ok(12));
+        // A number ending the file with no newline after it still counts
+        assert_eq!(part1_bytes(b"..*\n.12"), Ok(12));
+        assert_eq!(part2_bytes(b"1*2\n..3"), Ok(0));
+        assert_eq!(part2_bytes(b"1*.\n..3"), Ok(3));
+        assert!(part1_bytes(b"..*\n.1\n")
+            .unwrap_err()
+            .starts_with("Schematic is not rectangular"));
+    }
+
+    #[test]
+    fn rules_parse() {
+        assert_eq!("part2".parse(), Ok(Rules::part2()));
+        let rules: Rules = "neighbours=3,combine=sum".parse().unwrap();
+        let gear = rules.gear.unwrap();
+        assert_eq!((gear.chars, gear.neighbours), (vec![b'*'], 3));
+        assert_eq!(
+            "symbols=#$".parse::<Rules>().unwrap().symbols,
+            Some(b"#$".to_vec())
+        );
+        assert!("symbols=#,gears=*".parse::<Rules>().is_err());
+        assert!("part2,symbols=#".parse::<Rules>().is_err());
+        assert!("symbols=,".parse::<Rules>().is_err());
+        assert!("gears=".parse::<Rules>().is_err());
+        assert!("symbols=#1".parse::<Rules>().is_err());
+        assert!("symbols=*,#".parse::<Rules>().is_err());
+    }
 }
@@ -0,0 +1,107 @@
+use super::{adjacency, load, parse_nums, Rules};
+use crate::grid::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Blank,
+    PartNumber,
+    IgnoredNumber,
+    Gear,
+    Symbol,
+}
+
+impl Class {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Class::Blank => "\x1b[2m",
+            Class::PartNumber => "\x1b[32m",
+            Class::IgnoredNumber => "\x1b[31m",
+            Class::Gear => "\x1b[1;33m",
+            Class::Symbol => "\x1b[36m",
+        }
+    }
+
+    /// Marker drawn under the cell in plain text mode.
+    fn marker(&self) -> u8 {
+        match self {
+            Class::Blank => b' ',
+            Class::PartNumber => b'^',
+            Class::IgnoredNumber => b'-',
+            Class::Gear => b'G',
+            Class::Symbol => b'S',
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Draws the schematic with part numbers, ignored numbers, gears with
+/// exactly two neighbours and other symbols told apart. With `color`, they
+/// get ANSI colors; otherwise each row is followed by a row of markers.
+pub fn render(input_path: &str, color: bool) -> Result<String, String> {
+    let rules = Rules::part2();
+    let grid = load(input_path)?;
+    let (nums, gears) = parse_nums(&grid, &rules)?;
+    let adjacency = adjacency(&grid, &nums, |c| rules.is_symbol(c));
+
+    let mut classes: Grid<Class> = Grid::new(
+        grid.width(),
+        grid.height(),
+        vec![Class::Blank; grid.width() * grid.height()],
+    )?;
+    for pos in adjacency.keys() {
+        classes[*pos] = Class::Symbol;
+    }
+    for gear in gears.iter() {
+        if adjacency[&(gear.x_pos, gear.y_pos)].len() == 2 {
+            classes[(gear.x_pos, gear.y_pos)] = Class::Gear;
+        }
+    }
+    let mut is_part = vec![false; nums.len()];
+    for id in adjacency.values().flatten() {
+        is_part[*id] = true;
+    }
+    for (num, is_part) in nums.iter().zip(is_part) {
+        for x in num.x_start..num.x_start + num.len {
+            classes[(x, num.y_start)] = if is_part {
+                Class::PartNumber
+            } else {
+                Class::IgnoredNumber
+            };
+        }
+    }
+
+    let mut out = String::new();
+    for (row, class_row) in grid.rows().zip(classes.rows()) {
+        if color {
+            for (c, class) in row.iter().zip(class_row) {
+                out.push_str(class.ansi());
+                out.push(*c as char);
+                out.push_str(RESET);
+            }
+            out.push('\n');
+        } else {
+            out.push_str(&String::from_utf8_lossy(row));
+            out.push('\n');
+            let markers: Vec<u8> = class_row.iter().map(|class| class.marker()).collect();
+            out.push_str(String::from_utf8_lossy(&markers).trim_end());
+            out.push('\n');
+        }
+    }
+    let legend = [
+        (Class::PartNumber, "part number"),
+        (Class::IgnoredNumber, "ignored number"),
+        (Class::Gear, "gear"),
+        (Class::Symbol, "symbol"),
+    ];
+    let legend: Vec<String> = legend
+        .iter()
+        .map(|(class, name)| match color {
+            true => format!("{}{}{}", class.ansi(), name, RESET),
+            false => format!("{} {}", class.marker() as char, name),
+        })
+        .collect();
+    out.push_str(&legend.join(", "));
+    out.push('\n');
+    Ok(out)
+}
@@ -0,0 +1,110 @@
+use super::{number_cells, parse, parse_nums, MatchedNum, Rules};
+use crate::{grid::Grid, repl::Commands};
+
+/// The schematic with its numbers, found with part1's symbols.
+pub struct Session {
+    grid: Grid<u8>,
+    nums: Vec<MatchedNum>,
+    num_at: Grid<Option<usize>>,
+    part1: Rules,
+    part2: Rules,
+}
+
+impl Session {
+    pub fn new(input: &[u8]) -> Result<Session, String> {
+        let grid = parse(input)?;
+        let part1 = Rules::part1();
+        let (nums, _) = parse_nums(&grid, &part1)?;
+        let num_at = number_cells(&grid, &nums);
+        Ok(Session {
+            grid,
+            nums,
+            num_at,
+            part1,
+            part2: Rules::part2(),
+        })
+    }
+
+    /// What is at column `x` of line `y`, both from 0: the number it is part
+    /// of and the symbols that number touches, or the numbers a symbol
+    /// touches and its gear ratio.
+    fn at(&self, x: usize, y: usize) -> Result<String, String> {
+        let c = *self.grid.get(x, y).ok_or(format!(
+            "({},{}) is outside the {}x{} schematic",
+            x,
+            y,
+            self.grid.width(),
+            self.grid.height()
+        ))?;
+        let cell = format!("({},{}) '{}'", x, y, c as char);
+
+        if let Some(id) = self.num_at[(x, y)] {
+            let num = &self.nums[id];
+            let mut symbols: Vec<(usize, usize)> = (num.x_start..num.x_start + num.len)
+                .flat_map(|x| self.grid.neighbours8(x, num.y_start))
+                .filter(|pos| self.part1.is_symbol(self.grid[*pos]))
+                .collect();
+            symbols.sort_unstable_by_key(|(x, y)| (*y, *x));
+            symbols.dedup();
+            if symbols.is_empty() {
+                return Ok(format!("{}: {}, not a part number", cell, num.num));
+            }
+            let symbols: Vec<String> = symbols
+                .iter()
+                .map(|(x, y)| format!("'{}' at ({},{})", self.grid[(*x, *y)] as char, x, y))
+                .collect();
+            return Ok(format!(
+                "{}: {}, a part number, touching {}",
+                cell,
+                num.num,
+                symbols.join(", ")
+            ));
+        }
+
+        if !self.part1.is_symbol(c) {
+            return Ok(cell);
+        }
+        let mut ids: Vec<usize> = self
+            .grid
+            .neighbours8(x, y)
+            .filter_map(|pos| self.num_at[pos])
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.is_empty() {
+            return Ok(format!("{}: touching no numbers", cell));
+        }
+        let values: Vec<u64> = ids.iter().map(|id| self.nums[*id].num as u64).collect();
+        let touching: Vec<String> = values.iter().map(u64::to_string).collect();
+        let mut answer = format!("{}: touching {}", cell, touching.join(", "));
+        if let Some(gear_rule) = self.part2.gear.as_ref() {
+            if self.part2.is_gear(c) && ids.len() == gear_rule.neighbours {
+                let ratio = gear_rule
+                    .value(values.into_iter())
+                    .ok_or("Gear ratio overflowed".to_string())?;
+                answer += &format!(", a gear with ratio {}", ratio);
+            }
+        }
+        Ok(answer)
+    }
+}
+
+impl Commands for Session {
+    fn help(&self) -> &'static str {
+        "at <x>,<y>  the cell at column x of line y, from 0, with its number or the numbers it touches\n"
+    }
+
+    fn run(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["at", position] => {
+                let bad = || format!("Bad position {}, expected x,y", position);
+                let (x, y) = position.split_once(',').ok_or_else(bad)?;
+                let x = x.trim().parse().map_err(|_| bad())?;
+                let y = y.trim().parse().map_err(|_| bad())?;
+                self.at(x, y)
+            }
+            [command, ..] => Err(format!("Unknown command {}, try help", command)),
+            [] => Ok(String::new()),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub chars: Vec<u8>,
+    /// Exact number of adjacent numbers a gear needs
+    pub neighbours: usize,
+    pub combine: Combine,
+}
+
+impl GearRule {
+    /// The gear's value from its neighbouring numbers, None on overflow.
+    pub fn value(&self, mut values: impl Iterator<Item = u64>) -> Option<u64> {
+        match self.combine {
+            Combine::Product => values.try_fold(1u64, u64::checked_mul),
+            Combine::Sum => values.try_fold(0u64, u64::checked_add),
+        }
+    }
+}
+
+/// What counts as a symbol and what gets scored. Without a gear rule, the
+/// score is the sum of every number next to a symbol (part1). With one, it is
+/// the sum over gears of their neighbours combined (part2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// None means anything other than a digit or '.'
+    pub symbols: Option<Vec<u8>>,
+    pub gear: Option<GearRule>,
+}
+
+impl Rules {
+    pub fn part1() -> Rules {
+        Rules {
+            symbols: None,
+            gear: None,
+        }
+    }
+
+    pub fn part2() -> Rules {
+        Rules {
+            symbols: None,
+            gear: Some(GearRule {
+                chars: vec![b'*'],
+                neighbours: 2,
+                combine: Combine::Product,
+            }),
+        }
+    }
+
+    pub fn is_symbol(&self, c: u8) -> bool {
+        match &self.symbols {
+            Some(symbols) => symbols.contains(&c),
+            None => !matches!(c, b'0'..=b'9' | b'.'),
+        }
+    }
+
+    pub fn is_gear(&self, c: u8) -> bool {
+        self.gear
+            .as_ref()
+            .is_some_and(|gear| gear.chars.contains(&c))
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    /// Comma separated, optionally starting with a preset (part1 or part2)
+    /// that the other entries override: `symbols=<chars>`, `gears=<chars>`,
+    /// `neighbours=<n>` and `combine=product|sum`. Setting any gear entry
+    /// turns on gear scoring, defaulting to part2's gear rule. Gear scoring
+    /// ignores symbols, so `symbols=` with a gear rule is an error. Symbol and
+    /// gear chars cannot be digits, or ',' since it separates the entries.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .peekable();
+        let mut rules = match entries.next_if(|entry| *entry == "part1" || *entry == "part2") {
+            Some("part2") => Rules::part2(),
+            _ => Rules::part1(),
+        };
+
+        for entry in entries {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or(format!("Bad rule {:?}, expected key=value", entry))?;
+            if key == "symbols" {
+                rules.symbols = Some(parse_chars(key, value)?);
+                continue;
+            }
+            let gear = rules
+                .gear
+                .get_or_insert_with(|| Rules::part2().gear.unwrap());
+            match key {
+                "gears" => gear.chars = parse_chars(key, value)?,
+                "neighbours" => {
+                    gear.neighbours = value
+                        .parse()
+                        .map_err(|e| format!("Bad neighbour count {:?}: {}", value, e))?
+                }
+                "combine" => {
+                    gear.combine = match value {
+                        "product" => Combine::Product,
+                        "sum" => Combine::Sum,
+                        _ => return Err(format!("Unknown combine rule: {}", value)),
+                    }
+                }
+                _ => return Err(format!("Unknown rule: {}", key)),
+            }
+        }
+        if rules.symbols.is_some() && rules.gear.is_some() {
+            return Err("symbols= has no effect when scoring gears".to_string());
+        }
+        Ok(rules)
+    }
+}
+
+fn parse_chars(key: &str, value: &str) -> Result<Vec<u8>, String> {
+    if value.is_empty() {
+        return Err(format!(
+            "{}= needs at least one char, and ',' cannot be one",
+            key
+        ));
+    }
+    if value.bytes().any(|c| c.is_ascii_digit()) {
+        return Err(format!("{}= cannot include digits: {}", key, value));
+    }
+    Ok(value.as_bytes().to_vec())
+}
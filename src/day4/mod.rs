@@ -1,75 +1,318 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    io::BufRead,
+};
+
 #[derive(Debug)]
 struct Card {
-    winning_nums: Vec<usize>,
-    player_nums: Vec<usize>,
+    // Counted once when the card is parsed, it is all either part needs
+    num_winners: usize,
 }
 
-impl Card {
-    fn value(&self) -> usize {
-        let num_winning = self
-            .player_nums
+/// Player numbers that are also winning numbers. Uses a 128 bit set when
+/// every winning number fits, as in the puzzle input, else a hash set.
+fn count_winners(winning_nums: &[usize], player_nums: &[usize]) -> usize {
+    if winning_nums.iter().all(|num| *num < 128) {
+        let winning = winning_nums
             .iter()
-            .filter(|num| self.winning_nums.contains(num))
-            .count();
-        if num_winning == 0 {
-            return 0;
-        }
+            .fold(0u128, |bits, num| bits | (1 << num));
+        player_nums
+            .iter()
+            .filter(|num| **num < 128 && winning & (1 << **num) != 0)
+            .count()
+    } else {
+        let winning: HashSet<&usize> = winning_nums.iter().collect();
+        player_nums
+            .iter()
+            .filter(|num| winning.contains(num))
+            .count()
+    }
+}
 
-        2_usize.pow((num_winning - 1) as u32)
+impl Card {
+    fn new(winning_nums: &[usize], player_nums: &[usize]) -> Card {
+        Card {
+            num_winners: count_winners(winning_nums, player_nums),
+        }
     }
-    fn num_winners(&self) -> usize {
-        self.player_nums
-            .iter()
-            .filter(|num| self.winning_nums.contains(num))
-            .count() as usize
+
+    fn value(&self) -> Option<u128> {
+        if self.num_winners == 0 {
+            return Some(0);
+        }
+
+        2_u128.checked_pow((self.num_winners - 1) as u32)
     }
 }
 
-fn line_to_card(line: &str) -> Card {
-    let (_, num_list) = line.split_once(": ").unwrap();
-    let (winning_num_str, player_num_str) = num_list.split_once(" | ").unwrap();
+fn line_to_card(line: &str) -> Result<Card, String> {
+    let (_, num_list) = line
+        .split_once(": ")
+        .ok_or(format!("No \": \" in card {:?}", line))?;
+    let (winning_num_str, player_num_str) = num_list
+        .split_once(" | ")
+        .ok_or(format!("No \" | \" in card {:?}", line))?;
 
     let process_nums = |num_str: &str| {
         num_str
             .split(" ")
             .filter(|num_str| !num_str.is_empty())
-            .map(|num_str| num_str.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>()
+            .map(|num_str| {
+                num_str
+                    .parse::<usize>()
+                    .map_err(|e| format!("Bad number {:?} in card {:?}: {}", num_str, line, e))
+            })
+            .collect::<Result<Vec<usize>, String>>()
     };
 
-    Card {
-        winning_nums: process_nums(winning_num_str),
-        player_nums: process_nums(player_num_str),
-    }
+    Ok(Card::new(
+        &process_nums(winning_num_str)?,
+        &process_nums(player_num_str)?,
+    ))
+}
+
+fn parse(input: &str) -> Result<Vec<Card>, String> {
+    input.lines().map(line_to_card).collect()
 }
 
-pub fn part1(input_path: &str) -> String {
-    let input = std::fs::read_to_string(input_path).unwrap();
-    let cards: Vec<Card> = input.lines().map(line_to_card).collect();
+pub fn part1(input_path: &str) -> Result<u128, String> {
+    part1_str(&read(input_path)?)
+}
+
+pub fn part1_str(input: &str) -> Result<u128, String> {
+    let cards = parse(input)?;
 
     cards
         .iter()
-        .map(|card| card.value())
-        .sum::<usize>()
-        .to_string()
+        .enumerate()
+        .try_fold(0u128, |total, (idx, card)| add_value(total, idx, card))
+}
+
+fn add_value(total: u128, idx: usize, card: &Card) -> Result<u128, String> {
+    card.value()
+        .and_then(|value| total.checked_add(value))
+        .ok_or(format!("Total overflowed at card {}", idx + 1))
+}
+
+/// Cards read through `reader` one line at a time.
+fn cards(reader: impl BufRead) -> impl Iterator<Item = Result<Card, String>> {
+    reader.lines().map(|line| {
+        line.map_err(|e| format!("File read error: {}", e))
+            .and_then(|line| line_to_card(&line))
+    })
+}
+
+/// Same as `part1`, reading through `reader` a card at a time.
+pub fn part1_reader(reader: impl BufRead) -> Result<u128, String> {
+    let mut total = 0;
+    for (idx, card) in cards(reader).enumerate() {
+        total = add_value(total, idx, &card?)?;
+    }
+    Ok(total)
 }
 
-pub fn part2(input_path: &str) -> String {
-    let input = std::fs::read_to_string(input_path).unwrap();
-    let cards: Vec<Card> = input.lines().map(line_to_card).collect();
+/// What to do when a card wins copies of cards past the end of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PastEnd {
+    /// Copy only the cards that exist
+    Clamp,
+    Error,
+}
+
+/// One card's turn in the part2 cascade. Card numbers are 1-based.
+#[derive(Debug)]
+pub struct CascadeStep {
+    pub card: usize,
+    pub num_winners: usize,
+    pub copies: u128,
+    // Cards this one added copies to, empty if it won nothing
+    pub copied_into: std::ops::RangeInclusive<usize>,
+    // Won copies that ran past the last card and were clamped off
+    pub clamped: usize,
+}
 
-    let mut total_count = 0;
+impl std::fmt::Display for CascadeStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Card {}: {} winners, {} copies",
+            self.card, self.num_winners, self.copies
+        )?;
+        let (first, last) = (self.copied_into.start(), self.copied_into.end());
+        if first == last {
+            write!(f, " -> card {}", first)?;
+        } else if first < last {
+            write!(f, " -> cards {}-{}", first, last)?;
+        }
+        if self.clamped > 0 {
+            write!(f, " ({} past the end)", self.clamped)?;
+        }
+        Ok(())
+    }
+}
+
+/// Total scratchcards once every copy is processed. Counts are u128 and
+/// checked, as they grow exponentially with the number of winners.
+fn cascade(
+    cards: &[Card],
+    past_end: PastEnd,
+    mut trace: Option<&mut Vec<CascadeStep>>,
+) -> Result<u128, String> {
+    let mut total_count: u128 = 0;
     // each element is the count of cards
-    let mut active_cards: Vec<usize> = vec![1; cards.len()];
+    let mut active_cards: Vec<u128> = vec![1; cards.len()];
 
     for idx in 0..cards.len() {
         let card_count = active_cards[idx];
-        total_count += card_count;
-        let num_winners = cards[idx].num_winners();
-        for to_update_idx in (idx + 1)..(idx + 1 + num_winners) {
-            active_cards[to_update_idx] += card_count;
+        total_count = total_count
+            .checked_add(card_count)
+            .ok_or(format!("Total overflowed at card {}", idx + 1))?;
+        let num_winners = cards[idx].num_winners;
+        let copy_end = (idx + 1 + num_winners).min(cards.len());
+        let clamped = idx + 1 + num_winners - copy_end;
+        if clamped > 0 && past_end == PastEnd::Error {
+            return Err(format!(
+                "Card {} wins {} cards but only {} follow it",
+                idx + 1,
+                num_winners,
+                cards.len() - idx - 1
+            ));
+        }
+        for (offset, to_update) in active_cards[(idx + 1)..copy_end].iter_mut().enumerate() {
+            *to_update = to_update
+                .checked_add(card_count)
+                .ok_or(format!("Copies of card {} overflowed", idx + offset + 2))?;
+        }
+        if let Some(trace) = trace.as_deref_mut() {
+            trace.push(CascadeStep {
+                card: idx + 1,
+                num_winners,
+                copies: card_count,
+                // 1-based, so idx + 2 is the card after this one
+                copied_into: (idx + 2)..=copy_end,
+                clamped,
+            });
+        }
+    }
+
+    Ok(total_count)
+}
+
+fn read(input_path: &str) -> Result<String, String> {
+    std::fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))
+}
+
+pub fn part2(input_path: &str, past_end: PastEnd) -> Result<u128, String> {
+    part2_str(&read(input_path)?, past_end)
+}
+
+pub fn part2_str(input: &str, past_end: PastEnd) -> Result<u128, String> {
+    cascade(&parse(input)?, past_end, None)
+}
+
+/// Same as `part2`, reading through `reader` a card at a time. Only the counts
+/// of the cards a previous card has won copies of are kept, so memory is
+/// bounded by the most winners on a card rather than by the number of cards.
+pub fn part2_reader(reader: impl BufRead, past_end: PastEnd) -> Result<u128, String> {
+    let mut total_count: u128 = 0;
+    // Counts of the cards after the current one, None once they overflow.
+    // Cards without an entry have just the original.
+    let mut coming: VecDeque<Option<u128>> = VecDeque::new();
+    // (index, last index it copies into) of each card reaching further than
+    // all cards before it and past the current one. Once the last card is
+    // read, the first left is the first card that wins past the end.
+    let mut furthest: VecDeque<(usize, usize)> = VecDeque::new();
+
+    let mut num_cards = 0;
+    for (idx, card) in cards(reader).enumerate() {
+        let num_winners = card?.num_winners;
+        num_cards = idx + 1;
+        let card_count = coming
+            .pop_front()
+            .unwrap_or(Some(1))
+            .ok_or(format!("Copies of card {} overflowed", idx + 1))?;
+        total_count = total_count
+            .checked_add(card_count)
+            .ok_or(format!("Total overflowed at card {}", idx + 1))?;
+
+        if coming.len() < num_winners {
+            coming.resize(num_winners, Some(1));
         }
+        for to_update in coming.iter_mut().take(num_winners) {
+            *to_update = to_update.and_then(|count| count.checked_add(card_count));
+        }
+
+        while furthest.front().is_some_and(|(_, reach)| *reach <= idx) {
+            furthest.pop_front();
+        }
+        if furthest
+            .back()
+            .is_none_or(|(_, reach)| *reach < idx + num_winners)
+        {
+            furthest.push_back((idx, idx + num_winners));
+        }
+    }
+
+    if past_end == PastEnd::Error {
+        if let Some((idx, reach)) = furthest.iter().find(|(_, reach)| *reach >= num_cards) {
+            return Err(format!(
+                "Card {} wins {} cards but only {} follow it",
+                idx + 1,
+                reach - idx,
+                num_cards - idx - 1
+            ));
+        }
+    }
+    Ok(total_count)
+}
+
+/// Runs the part2 cascade, returning every card's step and the total.
+pub fn trace(input_path: &str, past_end: PastEnd) -> Result<(Vec<CascadeStep>, u128), String> {
+    let mut steps = Vec::new();
+    let total = cascade(&parse(&read(input_path)?)?, past_end, Some(&mut steps))?;
+    Ok((steps, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEAR_END: &str = "Card 1: 1 2 | 1 2\nCard 2: 3 | 4";
+
+    #[test]
+    fn wins_past_the_end() {
+        let error = Err("Card 1 wins 2 cards but only 1 follow it".to_string());
+        assert_eq!(part2_str(NEAR_END, PastEnd::Error), error);
+        assert_eq!(part2_reader(NEAR_END.as_bytes(), PastEnd::Error), error);
+        assert_eq!(part2_str(NEAR_END, PastEnd::Clamp), Ok(3));
+        assert_eq!(part2_reader(NEAR_END.as_bytes(), PastEnd::Clamp), Ok(3));
+
+        let mut steps = Vec::new();
+        let total = cascade(&parse(NEAR_END).unwrap(), PastEnd::Clamp, Some(&mut steps));
+        assert_eq!(total, Ok(3));
+        let shown: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+        assert_eq!(
+            shown,
+            [
+                "Card 1: 2 winners, 1 copies -> card 2 (1 past the end)",
+                "Card 2: 0 winners, 2 copies",
+            ]
+        );
+        assert_eq!(steps.iter().map(|step| step.clamped).sum::<usize>(), 1);
     }
 
-    total_count.to_string()
+    #[test]
+    fn copies_overflow() {
+        // Every card copying the next two grows the counts like Fibonacci
+        let cards = |count: usize| -> String {
+            (1..=count)
+                .map(|card| format!("Card {}: 1 2 | 1 2\n", card))
+                .collect()
+        };
+        assert!(part2_str(&cards(150), PastEnd::Clamp).is_ok());
+        let input = cards(200);
+        let in_memory = part2_str(&input, PastEnd::Clamp);
+        assert!(in_memory.as_ref().unwrap_err().contains("overflowed"));
+        assert_eq!(part2_reader(input.as_bytes(), PastEnd::Clamp), in_memory);
+    }
 }
@@ -8,6 +8,12 @@ use nom::{
 };
 use std::{collections::HashMap, fs};
 
+use crate::parallel;
+
+mod repl;
+
+pub use repl::Session;
+
 #[derive(Debug)]
 struct Range {
     destination: u64,
@@ -57,7 +63,7 @@ impl<'a> Almanac<'_> {
         }
     }
 
-    fn make_hop_iter(&'a self, location: &'a str, position: u64) -> LocationIterator {
+    fn make_hop_iter(&'a self, location: &'a str, position: u64) -> LocationIterator<'a> {
         LocationIterator {
             almanac: self,
             location,
@@ -72,33 +78,109 @@ impl<'a> Almanac<'_> {
 
         for range in source_category_map.ranges.iter() {
             if identifier >= range.source && identifier < range.source + range.length {
-                return Some((destination, identifier + range.destination - range.source));
+                return Some((destination, identifier - range.source + range.destination));
             }
         }
-        return Some((destination, identifier));
+        Some((destination, identifier))
+    }
+
+    fn location(&self, seed: u64) -> Result<u64, String> {
+        match self.make_hop_iter("seed", seed).last() {
+            Some(("location", position)) => Ok(position),
+            _ => Err(format!("Failed to find destination for seed {}", seed)),
+        }
     }
 
     fn find_min_location(&self, seeds: &[u64]) -> Result<u64, String> {
         let mut min_location = u64::MAX;
 
         for seed in seeds.iter() {
-            match self.make_hop_iter("seed", *seed).last() {
-                Some((category, position)) => {
-                    if category != "location" {
-                        return Result::Err(format!(
-                            "Failed to find destination for seed {}",
-                            *seed
-                        ));
-                    }
-                    min_location = min_location.min(position);
+            min_location = min_location.min(self.location(*seed)?);
+        }
+
+        Ok(min_location)
+    }
+
+    /// Same answer as `find_min_location_of_ranges`, trying every seed in
+    /// `seed_ranges` one at a time.
+    fn find_min_location_per_seed(&self, seed_ranges: &[(u64, u64)]) -> Result<u64, String> {
+        let mut min_location = u64::MAX;
+
+        for (start, end) in seed_ranges.iter() {
+            for seed in *start..*end {
+                min_location = min_location.min(self.location(seed)?);
+            }
+        }
+
+        Ok(min_location)
+    }
+
+    /// Maps each `start..end` range through one category map, splitting it
+    /// where it crosses the edges of the map's ranges. Like `next_hop`, the
+    /// first map range that covers an identifier wins.
+    fn next_hop_ranges(
+        &self,
+        source: &str,
+        ranges: Vec<(u64, u64)>,
+    ) -> Option<(&str, Vec<(u64, u64)>)> {
+        let source_category_map = self.maps.get(source)?;
+
+        let mut unmapped = ranges;
+        let mut mapped = Vec::new();
+        for range in source_category_map.ranges.iter() {
+            let range_end = range.source + range.length;
+            let mut rest = Vec::new();
+            for (start, end) in unmapped {
+                let overlap_start = start.max(range.source);
+                let overlap_end = end.min(range_end);
+                if overlap_start >= overlap_end {
+                    rest.push((start, end));
+                    continue;
+                }
+                mapped.push((
+                    overlap_start - range.source + range.destination,
+                    overlap_end - range.source + range.destination,
+                ));
+                if start < overlap_start {
+                    rest.push((start, overlap_start));
                 }
-                None => {
-                    return Result::Err(format!("Failed to find destination for seed {}", *seed));
+                if overlap_end < end {
+                    rest.push((overlap_end, end));
                 }
             }
+            unmapped = rest;
         }
+        mapped.extend(unmapped);
+        Some((source_category_map.destination_name, mapped))
+    }
 
-        Ok(min_location)
+    /// Same answer as `find_min_location` over every seed in `seed_ranges`,
+    /// but walks whole ranges through the maps instead of single seeds.
+    fn find_min_location_of_ranges(&self, seed_ranges: &[(u64, u64)]) -> Result<u64, String> {
+        let mut ranges: Vec<(u64, u64)> = seed_ranges
+            .iter()
+            .filter(|(start, end)| start < end)
+            .copied()
+            .collect();
+        if ranges.is_empty() {
+            return Ok(u64::MAX);
+        }
+
+        let mut category = "seed";
+        while let Some((next_category, next_ranges)) =
+            self.next_hop_ranges(category, ranges.clone())
+        {
+            category = next_category;
+            ranges = next_ranges;
+        }
+        if category != "location" {
+            return Err(format!(
+                "Failed to find destination for seeds in {}..{}",
+                ranges[0].0, ranges[0].1
+            ));
+        }
+
+        Ok(ranges.iter().map(|(start, _)| *start).min().unwrap())
     }
 }
 
@@ -124,7 +206,7 @@ fn parse_range(input: &str) -> IResult<&str, Range> {
     ))
 }
 
-fn parse_category_map(input: &str) -> IResult<&str, CategoryMap> {
+fn parse_category_map(input: &str) -> IResult<&str, CategoryMap<'_>> {
     let (remaining, (source_name, _, destination_name, _)) = tuple((
         take_until("-"),
         tag("-to-"),
@@ -145,7 +227,7 @@ fn parse_category_map(input: &str) -> IResult<&str, CategoryMap> {
     ))
 }
 
-fn parse(input: &str) -> IResult<&str, Almanac> {
+fn parse(input: &str) -> IResult<&str, Almanac<'_>> {
     let (remaining, seeds) = parse_seeds(input)?;
     let (remaining, map_vec) = separated_list0(tag("\n"), parse_category_map)(remaining)?;
 
@@ -154,32 +236,215 @@ fn parse(input: &str) -> IResult<&str, Almanac> {
     Ok((remaining, Almanac::new(seeds, maps)))
 }
 
+/// Parses the whole input and checks what the solvers rely on: no range runs
+/// past `u64::MAX`, and following the maps from seed never loops.
+fn parse_almanac(input: &str) -> Result<Almanac<'_>, String> {
+    let (remaining, almanac) = parse(input).map_err(|e| format!("Failed to parse input: {}", e))?;
+    if !remaining.is_empty() {
+        return Err(format!("Failed to parse input at {:?}", remaining));
+    }
+
+    for map in almanac.maps.values() {
+        for range in map.ranges.iter() {
+            if range.source.checked_add(range.length).is_none()
+                || range.destination.checked_add(range.length).is_none()
+            {
+                return Err(format!(
+                    "Range {} {} {} in {}-to-{} map is too long",
+                    range.destination,
+                    range.source,
+                    range.length,
+                    map.source_name,
+                    map.destination_name
+                ));
+            }
+        }
+    }
+    // Walks from any category, not just seed, must run out of maps within
+    // one hop per map, or the repl's map command would never finish
+    let mut starts: Vec<&str> = almanac.maps.keys().copied().collect();
+    starts.sort_unstable();
+    for start in starts {
+        let mut category = start;
+        for _ in 0..=almanac.maps.len() {
+            match almanac.maps.get(category) {
+                Some(map) => category = map.destination_name,
+                None => break,
+            }
+        }
+        if almanac.maps.contains_key(category) {
+            return Err(format!("Category maps loop back to {}", category));
+        }
+    }
+    Ok(almanac)
+}
+
 pub fn part1(input_path: &str) -> Result<u64, String> {
-    let input = fs::read_to_string(input_path).unwrap();
-    let (remaining, almanac) =
-        parse(&input).map_err(|e| format!("Failed to parse input: {}", e))?;
-    assert_eq!("", remaining);
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part1_str(&input)
+}
 
+pub fn part1_str(input: &str) -> Result<u64, String> {
+    let almanac = parse_almanac(input)?;
     almanac.find_min_location(almanac.seeds.as_slice())
 }
 
 pub fn part2(input_path: &str) -> Result<u64, String> {
-    let input = fs::read_to_string(input_path).unwrap();
-    let (remaining, almanac) =
-        parse(&input).map_err(|e| format!("Failed to parse input: {}", e))?;
-    assert_eq!("", remaining);
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part2_str(&input)
+}
+
+pub fn part2_str(input: &str) -> Result<u64, String> {
+    let almanac = parse_almanac(input)?;
+    almanac.find_min_location_of_ranges(&seed_ranges(&almanac.seeds)?)
+}
+
+/// Same as `part2`, walking the seed ranges on `threads` threads.
+pub fn part2_parallel(input: &str, threads: usize) -> Result<u64, String> {
+    let almanac = parse_almanac(input)?;
+    let seed_ranges = seed_ranges(&almanac.seeds)?;
+    min_of(parallel::map_chunks(&seed_ranges, threads, |seed_ranges| {
+        almanac.find_min_location_of_ranges(seed_ranges)
+    }))
+}
 
-    let mut min_location = u64::MAX;
-    // iterate over all the pairs of seeds
-    for chunk in almanac.seeds.chunks_exact(2) {
-        let start = chunk[0];
-        let length = chunk[1];
+/// Part2 the slow way, mapping every seed on its own, with the seeds split
+/// evenly across `threads` threads.
+pub fn part2_brute_force(input_path: &str, threads: usize) -> Result<u64, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part2_brute_force_str(&input, threads)
+}
 
-        let range = start..start + length;
-        let slice: Vec<u64> = range.collect();
+pub fn part2_brute_force_str(input: &str, threads: usize) -> Result<u64, String> {
+    let almanac = parse_almanac(input)?;
+    let shares = split_seeds(
+        &seed_ranges(&almanac.seeds)?,
+        parallel::thread_count(threads),
+    );
+    min_of(parallel::map_chunks(&shares, threads, |shares| {
+        min_of(
+            shares
+                .iter()
+                .map(|share| almanac.find_min_location_per_seed(share))
+                .collect(),
+        )
+    }))
+}
 
-        let location = almanac.find_min_location(&slice[..])?;
-        min_location = min_location.min(location);
+/// Lowest of `locations`, or the first error in order.
+fn min_of(locations: Vec<Result<u64, String>>) -> Result<u64, String> {
+    locations
+        .into_iter()
+        .try_fold(u64::MAX, |min, location| Ok(min.min(location?)))
+}
+
+/// Splits the seeds in `seed_ranges` into `shares` lists of ranges holding
+/// about the same number of seeds each.
+fn split_seeds(seed_ranges: &[(u64, u64)], shares: usize) -> Vec<Vec<(u64, u64)>> {
+    let total: u128 = seed_ranges
+        .iter()
+        .map(|(start, end)| end.saturating_sub(*start) as u128)
+        .sum();
+    let share_len = total.div_ceil(shares as u128).max(1);
+
+    let mut split = vec![Vec::new()];
+    let mut room = share_len;
+    for (start, end) in seed_ranges.iter() {
+        let mut start = *start;
+        while start < *end {
+            if room == 0 {
+                split.push(Vec::new());
+                room = share_len;
+            }
+            let take = room.min((end - start) as u128) as u64;
+            split.last_mut().unwrap().push((start, start + take));
+            start += take;
+            room -= take as u128;
+        }
+    }
+    split
+}
+
+/// Pairs of (start, length) in the seeds line as `start..end` ranges.
+fn seed_ranges(seeds: &[u64]) -> Result<Vec<(u64, u64)>, String> {
+    seeds
+        .chunks_exact(2)
+        .map(|chunk| {
+            let end = chunk[0]
+                .checked_add(chunk[1])
+                .ok_or(format!("Seed range {} {} is too long", chunk[0], chunk[1]))?;
+            Ok((chunk[0], end))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+
+    fn almanac(seeds: Vec<u64>, maps: Vec<Vec<(u64, u64, u64)>>) -> Almanac<'static> {
+        // seed -> soil -> ... -> location, skipping the middle categories
+        // that have no map
+        let mut names = CATEGORIES[..maps.len()].to_vec();
+        names.push("location");
+        let maps = maps
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ranges)| {
+                let map = CategoryMap {
+                    source_name: names[idx],
+                    destination_name: names[idx + 1],
+                    ranges: ranges
+                        .into_iter()
+                        .map(|(destination, source, length)| Range {
+                            destination,
+                            source,
+                            length,
+                        })
+                        .collect(),
+                };
+                (names[idx], map)
+            })
+            .collect();
+        Almanac::new(seeds, maps)
+    }
+
+    #[test]
+    fn loops_away_from_seed_are_rejected() {
+        let sample = fs::read_to_string("src/day5/part1_sample_input.txt").unwrap();
+        let looped = sample + "\n\nx-to-y map:\n1 2 3\n\ny-to-x map:\n2 1 3";
+        assert!(parse_almanac(&looped)
+            .unwrap_err()
+            .starts_with("Category maps loop back to"));
+    }
+
+    proptest! {
+        #[test]
+        fn range_walk_matches_per_seed(
+            seeds in prop::collection::vec(0u64..100, 0..8),
+            maps in prop::collection::vec(
+                prop::collection::vec((0u64..100, 0u64..100, 0u64..30), 0..5),
+                1..=7,
+            ),
+        ) {
+            let almanac = almanac(seeds, maps);
+            let ranges = seed_ranges(&almanac.seeds).unwrap();
+            prop_assert_eq!(
+                almanac.find_min_location_of_ranges(&ranges),
+                almanac.find_min_location_per_seed(&ranges)
+            );
+        }
     }
-    Ok(min_location)
 }
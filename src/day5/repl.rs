@@ -0,0 +1,58 @@
+use super::{parse_almanac, Almanac};
+use crate::repl::Commands;
+
+pub struct Session<'a> {
+    almanac: Almanac<'a>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(input: &'a str) -> Result<Session<'a>, String> {
+        Ok(Session {
+            almanac: parse_almanac(input)?,
+        })
+    }
+
+    /// `category value` and where each map after it sends the value.
+    fn map(&self, category: &str, value: u64) -> Result<String, String> {
+        let known = self
+            .almanac
+            .maps
+            .values()
+            .any(|map| map.source_name == category || map.destination_name == category);
+        if !known {
+            return Err(format!("Unknown category {}", category));
+        }
+        let mut path = format!("{} {}", category, value);
+        for (category, value) in self.almanac.make_hop_iter(category, value) {
+            path += &format!(" -> {} {}", category, value);
+        }
+        Ok(path)
+    }
+}
+
+impl Commands for Session<'_> {
+    fn help(&self) -> &'static str {
+        "map <category> <value>  the value's number in each category after, e.g. map seed 79\n\
+         seeds                   each seed and its location\n"
+    }
+
+    fn run(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["map", category, value] => {
+                let value = value
+                    .parse()
+                    .map_err(|e| format!("Bad value {}: {}", value, e))?;
+                self.map(category, value)
+            }
+            ["seeds"] => {
+                let mut lines = Vec::new();
+                for seed in self.almanac.seeds.iter() {
+                    lines.push(format!("{} -> {}", seed, self.almanac.location(*seed)?));
+                }
+                Ok(lines.join("\n"))
+            }
+            [command, ..] => Err(format!("Unknown command {}, try help", command)),
+            [] => Ok(String::new()),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use nom::{
     bytes::complete::tag,
     character::complete::{multispace1, u64},
+    combinator::{all_consuming, opt},
     multi::separated_list0,
     sequence::tuple,
     Finish, IResult,
@@ -20,20 +21,55 @@ fn parse_distances(input: &str) -> IResult<&str, Vec<u64>> {
 }
 
 fn parse(input: &str) -> Result<(Vec<u64>, Vec<u64>), nom::error::Error<&str>> {
-    let (remain, (times, _, distances)) =
-        tuple((parse_times, tag("\n"), parse_distances))(input).finish()?;
-    assert_eq!(remain, "");
+    let (_, (times, _, distances, _)) = all_consuming(tuple((
+        parse_times,
+        tag("\n"),
+        parse_distances,
+        opt(tag("\n")),
+    )))(input)
+    .finish()?;
     Ok((times, distances))
 }
 
+/// Number of whole-millisecond button holds that beat `distance` in a race of
+/// `time`. Holding for `t` travels `t * (time - t)`, a parabola symmetric
+/// about `time / 2`, so the winning holds are `lower..=time - lower` for the
+/// smallest winning `lower`. Its closed form is only a float estimate, so it is
+/// nudged onto the exact integer.
+fn ways_to_win(time: u64, distance: u64) -> u64 {
+    let travelled = |hold: u64| hold as u128 * (time - hold) as u128;
+    let half = time / 2;
+    let discriminant = (time as f64).powi(2) - 4.0 * distance as f64;
+    if discriminant < 0.0 {
+        return 0;
+    }
+    let mut lower = (((time as f64 - discriminant.sqrt()) / 2.0).ceil() as u64).min(half);
+    while lower > 0 && travelled(lower - 1) > distance as u128 {
+        lower -= 1;
+    }
+    while lower <= half && travelled(lower) <= distance as u128 {
+        lower += 1;
+    }
+    if lower > half {
+        return 0;
+    }
+    time - 2 * lower + 1
+}
+
 pub fn part1(input_path: &str) -> Result<u64, String> {
-    let input = fs::read_to_string(input_path).unwrap();
-    match parse(&input) {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part1_str(&input)
+}
+
+pub fn part1_str(input: &str) -> Result<u64, String> {
+    match parse(input) {
         Ok((times, distances)) => {
-            let races: Vec<_> = times.into_iter().zip(distances.into_iter()).collect();
+            let races: Vec<_> = times.into_iter().zip(distances).collect();
             let mut product = 1u64;
             for (time, distance) in races.iter() {
-                product *= (1..*time).filter(|t| (time - t) * t > *distance).count() as u64;
+                product = product
+                    .checked_mul(ways_to_win(*time, *distance))
+                    .ok_or("Product of ways to win overflowed")?;
             }
             Ok(product)
         }
@@ -49,13 +85,48 @@ fn vec_list_parse<F: FromStr, T: Display>(vec: Vec<T>) -> Result<F, F::Err> {
 
 pub fn part2(input_path: &str) -> Result<u64, String> {
     let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part2_str(&input)
+}
 
-    let (times, distances) = parse(&input).map_err(|e| format!("Parse Error: {}", e))?;
+pub fn part2_str(input: &str) -> Result<u64, String> {
+    let (times, distances) = parse(input).map_err(|e| format!("Parse Error: {}", e))?;
 
     let time: u64 = vec_list_parse(times).map_err(|e| format!("Number join error: {}", e))?;
 
     let distance: u64 =
         vec_list_parse(distances).map_err(|e| format!("Number join error: {}", e))?;
 
-    Ok((1..time).filter(|t| (time - t) * t > distance).count() as u64)
+    Ok(ways_to_win(time, distance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Tries every hold, as both parts used to.
+    fn ways_to_win_by_brute_force(time: u64, distance: u64) -> u64 {
+        (1..time).filter(|t| (time - t) * t > distance).count() as u64
+    }
+
+    proptest! {
+        #[test]
+        fn closed_form_matches_brute_force(time in 0u64..2000, distance in 0u64..1_100_000) {
+            prop_assert_eq!(
+                ways_to_win(time, distance),
+                ways_to_win_by_brute_force(time, distance)
+            );
+        }
+
+        #[test]
+        fn closed_form_matches_brute_force_near_best(time in 0u64..2000, below in 0u64..50) {
+            // Records just under the best distance leave few winning holds,
+            // where an off-by-one at the edges shows up
+            let distance = (time / 2 * (time - time / 2)).saturating_sub(below);
+            prop_assert_eq!(
+                ways_to_win(time, distance),
+                ways_to_win_by_brute_force(time, distance)
+            );
+        }
+    }
 }
@@ -0,0 +1,313 @@
+//! Parsing and ranking hands, shared by both parts. They differ only in
+//! whether J is a joker.
+
+use nom::{
+    bytes::complete::{tag, take},
+    character::complete::u64,
+    combinator::{all_consuming, verify},
+    sequence::tuple,
+    Finish, IResult,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    io::BufRead,
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum HandType {
+    HighCard = 1,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct Hand {
+    cards: [u8; 5],
+    bid: u64,
+}
+
+impl Hand {
+    /// With `jokers`, each J counts as whichever card makes the best hand.
+    fn hand_type(&self, jokers: bool) -> HandType {
+        let mut bins = HashMap::new();
+        for card in self.cards {
+            bins.insert(card, bins.get(&card).unwrap_or(&0) + 1);
+        }
+        let num_joker = match jokers {
+            true => *(bins.get(&b'J').unwrap_or(&0)),
+            false => 0,
+        };
+        if bins.len() == 1 {
+            HandType::FiveOfAKind
+        } else if bins.len() == 2 {
+            if num_joker > 0 {
+                HandType::FiveOfAKind
+            } else if bins.values().any(|v| *v == 4) {
+                HandType::FourOfAKind
+            } else {
+                HandType::FullHouse
+            }
+        } else if bins.len() == 3 {
+            if num_joker > 0 {
+                if bins.values().any(|v| *v == 3) || num_joker == 2 {
+                    HandType::FourOfAKind
+                } else {
+                    HandType::FullHouse
+                }
+            } else if bins.values().any(|v| *v == 3) {
+                HandType::ThreeOfAKind
+            } else {
+                HandType::TwoPair
+            }
+        } else if bins.len() == 4 {
+            if num_joker > 0 {
+                HandType::ThreeOfAKind
+            } else {
+                HandType::OnePair
+            }
+        } else if num_joker > 0 {
+            HandType::OnePair
+        } else {
+            HandType::HighCard
+        }
+    }
+
+    /// Orders hands weakest first: by type, then card by card.
+    fn strength(&self, jokers: bool) -> (HandType, [u8; 5]) {
+        (
+            self.hand_type(jokers),
+            self.cards.map(|card| card_value(card, jokers)),
+        )
+    }
+}
+
+fn card_value(card: u8, jokers: bool) -> u8 {
+    match card {
+        b'2'..=b'9' => card - b'0',
+        b'T' => 10,
+        b'J' if jokers => 1,
+        b'J' => 11,
+        b'Q' => 12,
+        b'K' => 13,
+        b'A' => 14,
+        // The parser only lets the cards above through
+        _ => 0,
+    }
+}
+
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let card_str: String = self.cards.iter().map(|c| *c as char).collect();
+        write!(f, "{} {}", card_str, self.bid)
+    }
+}
+
+fn parse_cards(input: &str) -> IResult<&str, [u8; 5]> {
+    let is_card = |card_str: &str| card_str.bytes().all(|c| b"23456789TJQKA".contains(&c));
+    let (remain, card_str) = verify(take(5u32), is_card)(input)?;
+    let mut cards = [0u8; 5];
+    cards
+        .iter_mut()
+        .zip(card_str.chars())
+        .for_each(|(c, ch)| *c = ch as u8);
+    Ok((remain, cards))
+}
+
+fn parse_hand(input: &str) -> IResult<&str, Hand> {
+    let (remain, (cards, _, bid)) = tuple((parse_cards, tag(" "), u64))(input)?;
+    Ok((remain, Hand { cards, bid }))
+}
+
+/// Hands gathered a line at a time, so the in-memory and streaming parsers
+/// accept exactly the same inputs. Blank lines are only allowed at the end,
+/// and no hand may repeat.
+#[derive(Default)]
+struct HandLines {
+    hands: Vec<Hand>,
+    seen: HashSet<[u8; 5]>,
+    blank_line: Option<usize>,
+}
+
+impl HandLines {
+    fn add(&mut self, line_idx: usize, line: &str) -> Result<(), String> {
+        if line.trim().is_empty() {
+            self.blank_line.get_or_insert(line_idx + 1);
+            return Ok(());
+        }
+        if let Some(blank_line) = self.blank_line {
+            return Err(format!(
+                "Parse Error: blank line {} before a hand",
+                blank_line
+            ));
+        }
+        let (_, hand) = all_consuming(parse_hand)(line.trim_end())
+            .finish()
+            .map_err(|e| format!("Parse Error at line {}: {}", line_idx + 1, e))?;
+        if !self.seen.insert(hand.cards) {
+            return Err(format!("Hand {} appears more than once", hand));
+        }
+        self.hands.push(hand);
+        Ok(())
+    }
+}
+
+pub(super) fn parse(input: &str) -> Result<Vec<Hand>, String> {
+    let mut hands = HandLines::default();
+    for (line_idx, line) in input.lines().enumerate() {
+        hands.add(line_idx, line)?;
+    }
+    Ok(hands.hands)
+}
+
+/// Same as `parse`, reading through `reader` a hand at a time. Hands may not
+/// repeat, so however long the input, at most 13^5 of them are kept.
+pub(super) fn read(reader: impl BufRead) -> Result<Vec<Hand>, String> {
+    let mut hands = HandLines::default();
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("File read error: {}", e))?;
+        hands.add(line_idx, &line)?;
+    }
+    Ok(hands.hands)
+}
+
+/// `hands` weakest first.
+fn rank(mut hands: Vec<Hand>, jokers: bool) -> Vec<Hand> {
+    hands.sort_by_cached_key(|hand| hand.strength(jokers));
+    hands
+}
+
+pub(super) fn total_winnings(hands: Vec<Hand>, jokers: bool) -> Result<u64, String> {
+    rank(hands, jokers)
+        .iter()
+        .enumerate()
+        .try_fold(0u64, |acc, (i, h)| {
+            h.bid
+                .checked_mul((i + 1) as u64)
+                .and_then(|winnings| acc.checked_add(winnings))
+        })
+        .ok_or("Total winnings overflowed".to_string())
+}
+
+/// Where a hand ranks among the input's hands.
+pub(super) struct Placement {
+    pub hand_type: String,
+    pub rank: usize,
+    /// Number of hands ranked, counting this one
+    pub of: usize,
+    /// Set when the hand is in the input
+    pub bid: Option<u64>,
+}
+
+/// The input's hands in rank order, for placing other hands among them.
+pub(super) struct Ranking {
+    hands: Vec<Hand>,
+    jokers: bool,
+}
+
+impl Ranking {
+    pub(super) fn new(input: &str, jokers: bool) -> Result<Ranking, String> {
+        Ok(Ranking {
+            hands: rank(parse(input)?, jokers),
+            jokers,
+        })
+    }
+
+    /// Where `cards` rank among the input's hands.
+    pub(super) fn place(&self, cards: &str) -> Result<Placement, String> {
+        let (_, cards) = all_consuming(parse_cards)(cards)
+            .finish()
+            .map_err(|_| format!("Bad hand {}, expected 5 of 23456789TJQKA", cards))?;
+        let hand = Hand { cards, bid: 0 };
+        let strength = hand.strength(self.jokers);
+        let bid = self
+            .hands
+            .iter()
+            .find(|other| other.cards == cards)
+            .map(|other| other.bid);
+        let weaker = self
+            .hands
+            .iter()
+            .filter(|other| other.strength(self.jokers) < strength)
+            .count();
+        Ok(Placement {
+            hand_type: format!("{:?}", hand.hand_type(self.jokers)),
+            rank: weaker + 1,
+            of: self.hands.len() + usize::from(bid.is_none()),
+            bid,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Hand type with no jokers, from how many of each card there are.
+    fn plain_hand_type(cards: &[u8; 5]) -> HandType {
+        let mut counts: Vec<usize> = cards
+            .iter()
+            .map(|card| cards.iter().filter(|c| *c == card).count())
+            .collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        match counts[..] {
+            [5, ..] => HandType::FiveOfAKind,
+            [4, ..] => HandType::FourOfAKind,
+            [3, 3, 3, 2, 2] => HandType::FullHouse,
+            [3, ..] => HandType::ThreeOfAKind,
+            [2, 2, 2, 2, 1] => HandType::TwoPair,
+            [2, ..] => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+
+    /// Best type over every way of turning each joker into any other card.
+    fn hand_type_by_substitution(cards: [u8; 5]) -> HandType {
+        match cards.iter().position(|card| *card == b'J') {
+            None => plain_hand_type(&cards),
+            Some(idx) => b"23456789TQKA"
+                .iter()
+                .map(|card| {
+                    let mut substituted = cards;
+                    substituted[idx] = *card;
+                    hand_type_by_substitution(substituted)
+                })
+                .max()
+                .unwrap(),
+        }
+    }
+
+    fn card() -> impl Strategy<Value = u8> {
+        // Jokers are weighted up so hands with several of them are common
+        prop_oneof![
+            1 => Just(b'J'),
+            3 => prop::sample::select(b"23456789TQKA".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn blank_lines_only_at_the_end() {
+        for input in ["32T3K 765\n\nT55J5 684\n", "\n32T3K 765\n", " \n32T3K 765"] {
+            assert_eq!(parse(input), read(input.as_bytes()), "{:?}", input);
+            assert!(parse(input).is_err(), "{:?}", input);
+        }
+        for input in ["32T3K 765\r\nT55J5 684\r\n\r\n", "32T3K 765\n \n\n", ""] {
+            assert_eq!(parse(input), read(input.as_bytes()), "{:?}", input);
+            assert!(parse(input).is_ok(), "{:?}", input);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn joker_classifier_matches_substitution(cards in prop::array::uniform5(card())) {
+            let hand = Hand { cards, bid: 0 };
+            prop_assert_eq!(hand.hand_type(true), hand_type_by_substitution(cards));
+            prop_assert_eq!(hand.hand_type(false), plain_hand_type(&cards));
+        }
+    }
+}
@@ -1,5 +1,8 @@
+mod hands;
 mod part1mod;
 mod part2mod;
+mod repl;
 
-pub use part1mod::part1;
-pub use part2mod::part2;
+pub use part1mod::{part1, part1_reader, part1_str};
+pub use part2mod::{part2, part2_reader, part2_str};
+pub use repl::Session;
@@ -0,0 +1,50 @@
+use super::hands::Ranking;
+use crate::repl::Commands;
+
+/// The input's hands ranked under both parts' rules.
+pub struct Session {
+    part1: Ranking,
+    part2: Ranking,
+}
+
+impl Session {
+    pub fn new(input: &str) -> Result<Session, String> {
+        Ok(Session {
+            part1: Ranking::new(input, false)?,
+            part2: Ranking::new(input, true)?,
+        })
+    }
+}
+
+impl Commands for Session {
+    fn help(&self) -> &'static str {
+        "rank <cards>  the hand's type and rank under each part's rules, e.g. rank KK677\n"
+    }
+
+    fn run(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["rank", cards] => {
+                let placements = [
+                    ("part1", self.part1.place(cards)?),
+                    ("part2", self.part2.place(cards)?),
+                ];
+                let lines: Vec<String> = placements
+                    .iter()
+                    .map(|(part, placement)| match placement.bid {
+                        Some(bid) => format!(
+                            "{}: {}, rank {} of {}, bid {}",
+                            part, placement.hand_type, placement.rank, placement.of, bid
+                        ),
+                        None => format!(
+                            "{}: {}, would rank {} of {}",
+                            part, placement.hand_type, placement.rank, placement.of
+                        ),
+                    })
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            [command, ..] => Err(format!("Unknown command {}, try help", command)),
+            [] => Ok(String::new()),
+        }
+    }
+}
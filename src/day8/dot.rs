@@ -0,0 +1,96 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use super::{ParsedNetwork, ParsedNode};
+
+/// Follows `instructions` from `start` over the parsed nodes, returning every
+/// (from, instruction, to) edge taken. Stops at `goal`, at a dangling edge, or
+/// once a (node, instruction index) pair repeats.
+fn walk<'a>(
+    nodes: &HashMap<&'a str, &ParsedNode<'a>>,
+    start: &'a str,
+    goal: &str,
+    instructions: &[u8],
+) -> Vec<(&'a str, u8, &'a str)> {
+    let mut taken = Vec::new();
+    let mut seen: HashSet<(&str, usize)> = HashSet::new();
+    let mut current = start;
+    let mut instructions_idx = 0;
+
+    while current != goal && !instructions.is_empty() && seen.insert((current, instructions_idx)) {
+        let Some(node) = nodes.get(current) else {
+            break;
+        };
+        let instruction = instructions[instructions_idx];
+        let next = match instruction {
+            b'L' => node.left,
+            b'R' => node.right,
+            _ => break,
+        };
+        taken.push((current, instruction, next));
+        current = next;
+        instructions_idx = (instructions_idx + 1) % instructions.len();
+    }
+    taken
+}
+
+/// Renders the network as a Graphviz digraph with L/R labelled edges.
+///
+/// `start` and `goal` are drawn as filled nodes. If `highlight` holds an
+/// instruction string, the walk it produces from `start` is drawn in red.
+pub(super) fn to_dot(
+    network: &ParsedNetwork,
+    start: &str,
+    goal: &str,
+    highlight: Option<&[u8]>,
+) -> String {
+    let nodes: HashMap<&str, &ParsedNode> = network.nodes.iter().map(|n| (n.name, n)).collect();
+
+    let taken = match highlight {
+        Some(instructions) => walk(&nodes, start, goal, instructions),
+        None => Vec::new(),
+    };
+    let path_edges: HashSet<(&str, u8)> = taken
+        .iter()
+        .map(|(from, instruction, _)| (*from, *instruction))
+        .collect();
+    let path_nodes: HashSet<&str> = taken
+        .iter()
+        .flat_map(|(from, _, to)| [*from, *to])
+        .collect();
+
+    let mut out = String::from("digraph network {\n");
+    for node in network.nodes.iter() {
+        let mut attrs = Vec::new();
+        if node.name == start {
+            attrs.push("shape=doublecircle, style=filled, fillcolor=palegreen");
+        } else if node.name == goal {
+            attrs.push("shape=doublecircle, style=filled, fillcolor=lightcoral");
+        }
+        if path_nodes.contains(node.name) {
+            attrs.push("color=red, penwidth=2");
+        }
+        if !attrs.is_empty() {
+            writeln!(out, "    \"{}\" [{}];", node.name, attrs.join(", ")).unwrap();
+        }
+    }
+    for node in network.nodes.iter() {
+        for (instruction, dest) in [(b'L', node.left), (b'R', node.right)] {
+            let style = if path_edges.contains(&(node.name, instruction)) {
+                ", color=red, fontcolor=red, penwidth=2"
+            } else {
+                ""
+            };
+            writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                node.name, dest, instruction as char, style
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("}\n");
+    out
+}
@@ -7,12 +7,23 @@ use std::{
 
 use nom::{
     bytes::complete::{is_a, tag},
-    character::complete::alpha1,
+    character::complete::alphanumeric1,
+    combinator::opt,
     multi::separated_list1,
     Finish, IResult,
 };
 use tailcall::tailcall;
 
+use crate::parallel;
+
+mod dot;
+mod repl;
+mod trace;
+
+pub use repl::Session;
+use trace::PathStep;
+pub use trace::PathTrace;
+
 #[derive(Debug)]
 struct ParsedNetwork<'a> {
     instructions: &'a [u8],
@@ -33,7 +44,12 @@ struct Node<'a> {
     right: Option<Rc<RefCell<Node<'a>>>>,
 }
 
-fn steps_to_dest(node: Rc<RefCell<Node>>, dest: &str, instructions: &[u8]) -> Result<u64, String> {
+fn steps_to_dest(
+    node: Rc<RefCell<Node>>,
+    dest: &dyn Fn(&str) -> bool,
+    instructions: &[u8],
+    trace: Option<&mut PathTrace>,
+) -> Result<u64, String> {
     let mut seen: HashSet<(String, usize)> = HashSet::new();
     // tailcall is necessary to avoid stack overflow
     #[tailcall]
@@ -41,9 +57,10 @@ fn steps_to_dest(node: Rc<RefCell<Node>>, dest: &str, instructions: &[u8]) -> Re
         accum: u64,
         seen: &mut HashSet<(String, usize)>,
         node: Rc<RefCell<Node>>,
-        dest: &str,
+        dest: &dyn Fn(&str) -> bool,
         instructions: &[u8],
         instructions_idx: usize,
+        mut trace: Option<&mut PathTrace>,
     ) -> Result<u64, String> {
         // Loop detector. Very important if you start at the wrong node
         let node = node.borrow();
@@ -56,27 +73,37 @@ fn steps_to_dest(node: Rc<RefCell<Node>>, dest: &str, instructions: &[u8]) -> Re
                 node.name, instructions[instructions_idx] as char, instructions_idx, accum,
             ));
         }
-        seen.insert((node.name.to_string(), instructions_idx as usize));
+        seen.insert((node.name.to_string(), instructions_idx));
 
-        if node.name == dest {
+        if dest(node.name) {
             Ok(accum)
         } else {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record(PathStep {
+                    index: accum,
+                    node: node.name.to_string(),
+                    instruction: instructions[instructions_idx] as char,
+                })?;
+            }
+            let missing = || format!("Node {} leads to a node that does not exist", node.name);
             match instructions[instructions_idx] {
                 b'L' => steps_to_dest_inner(
                     accum + 1,
                     seen,
-                    node.left.clone().unwrap(),
+                    node.left.clone().ok_or_else(missing)?,
                     dest,
                     instructions,
                     (instructions_idx + 1) % instructions.len(),
+                    trace,
                 ),
                 b'R' => steps_to_dest_inner(
                     accum + 1,
                     seen,
-                    node.right.clone().unwrap(),
+                    node.right.clone().ok_or_else(missing)?,
                     dest,
                     instructions,
                     (instructions_idx + 1) % instructions.len(),
+                    trace,
                 ),
                 _ => Err(format!(
                     "Invalid instruction {} for node {}",
@@ -85,10 +112,13 @@ fn steps_to_dest(node: Rc<RefCell<Node>>, dest: &str, instructions: &[u8]) -> Re
             }
         }
     }
-    steps_to_dest_inner(0, &mut seen, node, dest, instructions, 0)
+    steps_to_dest_inner(0, &mut seen, node, dest, instructions, 0, trace)
 }
 
-fn build_network<'a>(parsed_network: ParsedNetwork<'a>) -> Rc<RefCell<Node<'a>>> {
+fn build_network<'a>(
+    parsed_network: &ParsedNetwork<'a>,
+    start: &str,
+) -> Result<Rc<RefCell<Node<'a>>>, String> {
     let mut map: HashMap<&str, Rc<RefCell<Node>>> = HashMap::new();
     for node in parsed_network.nodes.iter() {
         map.insert(
@@ -102,10 +132,12 @@ fn build_network<'a>(parsed_network: ParsedNetwork<'a>) -> Rc<RefCell<Node<'a>>>
     }
     for parsed_node in parsed_network.nodes.iter() {
         let mut node = map.get(parsed_node.name).unwrap().borrow_mut();
-        node.left = map.get(parsed_node.left).map(|n| Rc::clone(n));
-        node.right = map.get(parsed_node.right).map(|n| Rc::clone(n));
+        node.left = map.get(parsed_node.left).map(Rc::clone);
+        node.right = map.get(parsed_node.right).map(Rc::clone);
     }
-    Rc::clone(map.get("AAA").unwrap())
+    map.get(start)
+        .map(Rc::clone)
+        .ok_or(format!("Start node {} not found", start))
 }
 
 fn parse_instructions(input: &str) -> IResult<&str, &[u8]> {
@@ -113,27 +145,21 @@ fn parse_instructions(input: &str) -> IResult<&str, &[u8]> {
     Ok((remains, instructions.as_bytes()))
 }
 
-fn parse_node(input: &str) -> IResult<&str, ParsedNode> {
-    let (remain, name) = alpha1(input)?;
+fn parse_node(input: &str) -> IResult<&str, ParsedNode<'_>> {
+    let (remain, name) = alphanumeric1(input)?;
     let (remain, _) = tag(" = (")(remain)?;
-    let (remain, left) = alpha1(remain)?;
+    let (remain, left) = alphanumeric1(remain)?;
     let (remain, _) = tag(", ")(remain)?;
-    let (remain, right) = alpha1(remain)?;
+    let (remain, right) = alphanumeric1(remain)?;
     let (remain, _) = tag(")")(remain)?;
-    Ok((
-        remain,
-        ParsedNode {
-            name: name,
-            left: left,
-            right: right,
-        },
-    ))
+    Ok((remain, ParsedNode { name, left, right }))
 }
 
-fn parse(input: &str) -> IResult<&str, ParsedNetwork> {
+fn parse(input: &str) -> IResult<&str, ParsedNetwork<'_>> {
     let (remain, instructions) = parse_instructions(input)?;
     let (remain, _) = tag("\n\n")(remain)?;
     let (remain, nodes) = separated_list1(tag("\n"), parse_node)(remain)?;
+    let (remain, _) = opt(tag("\n"))(remain)?;
     Ok((
         remain,
         ParsedNetwork {
@@ -144,22 +170,359 @@ fn parse(input: &str) -> IResult<&str, ParsedNetwork> {
 }
 
 pub fn part1(input_path: &str) -> Result<u64, String> {
-    let input = fs::read_to_string(input_path).unwrap();
-    let (remain, parsed_network) = parse(&input)
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part1_str(&input)
+}
+
+pub fn part1_str(input: &str) -> Result<u64, String> {
+    let (remain, parsed_network) = parse(input)
         .finish()
         .map_err(|e| format!("Parse Error: {}", e))?;
-    if remain != "" {
+    if !remain.is_empty() {
         return Err("Parse Error".to_string());
     }
     let instructions = parsed_network.instructions;
-    let network = build_network(parsed_network);
+    let network = build_network(&parsed_network, "AAA")?;
 
-    let steps = steps_to_dest(network, "ZZZ", instructions)?;
+    let steps = steps_to_dest(network, &|name| name == "ZZZ", instructions, None)?;
     Ok(steps)
 }
 
+/// Walks from `start` to `goal` like part1, recording each step into `trace`.
+pub fn trace(
+    input_path: &str,
+    start: &str,
+    goal: &str,
+    instructions: Option<&str>,
+    trace: &mut PathTrace,
+) -> Result<u64, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    let (_, parsed_network) = parse(&input)
+        .finish()
+        .map_err(|e| format!("Parse Error: {}", e))?;
+    let instructions = instructions
+        .map(|i| i.as_bytes())
+        .unwrap_or(parsed_network.instructions);
+    if instructions.is_empty() {
+        return Err("No instructions to follow".to_string());
+    }
+    if let Some(bad) = instructions.iter().find(|i| !matches!(i, b'L' | b'R')) {
+        return Err(format!("Instruction {:?} is not L or R", *bad as char));
+    }
+    let network = build_network(&parsed_network, start)?;
+
+    let steps = steps_to_dest(
+        network,
+        &|name| name == goal,
+        instructions,
+        Some(&mut *trace),
+    );
+    trace.finish()?;
+    steps
+}
+
+/// Renders the input network as Graphviz DOT. When `highlight` is set, the walk
+/// from `start` is traced using `instructions`, or the input's own instructions
+/// if none are given.
+pub fn dot(
+    input_path: &str,
+    start: &str,
+    goal: &str,
+    highlight: bool,
+    instructions: Option<&str>,
+) -> Result<String, String> {
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    let (_, parsed_network) = parse(&input)
+        .finish()
+        .map_err(|e| format!("Parse Error: {}", e))?;
+    let highlight = match (highlight, instructions) {
+        (false, _) => None,
+        (true, Some(instructions)) => Some(instructions.as_bytes()),
+        (true, None) => Some(parsed_network.instructions),
+    };
+    Ok(dot::to_dot(&parsed_network, start, goal, highlight))
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The steps at which a ghost starting on `start` is on a node ending in Z,
+/// as (first, period) pairs: every `first + k * period`, or only `first` when
+/// the period is 0. Found by walking until a (node, instruction) state
+/// repeats, since from then on the walk is a loop.
+fn ghost_hits(
+    nodes: &HashMap<&str, (&str, &str)>,
+    instructions: &[u8],
+    start: &str,
+) -> Result<Vec<(u128, u128)>, String> {
+    let mut first_seen: HashMap<(&str, usize), u128> = HashMap::new();
+    let mut hits = Vec::new();
+    let mut node = start;
+    let mut step: u128 = 0;
+    loop {
+        let idx = (step % instructions.len() as u128) as usize;
+        if let Some(&loop_start) = first_seen.get(&(node, idx)) {
+            let period = step - loop_start;
+            return Ok(hits
+                .into_iter()
+                .map(|hit| (hit, if hit >= loop_start { period } else { 0 }))
+                .collect());
+        }
+        first_seen.insert((node, idx), step);
+        if node.ends_with('Z') {
+            hits.push(step);
+        }
+        let (left, right) = nodes[node];
+        let next = match instructions[idx] {
+            b'L' => left,
+            _ => right,
+        };
+        if !nodes.contains_key(next) {
+            return Err(format!("Node {} leads to a node that does not exist", node));
+        }
+        node = next;
+        step += 1;
+    }
+}
+
+/// Is `step` one of the steps `first + k * period`?
+fn hits_at((first, period): (u128, u128), step: u128) -> bool {
+    match period {
+        0 => step == first,
+        _ => step >= first && (step - first).is_multiple_of(period),
+    }
+}
+
+/// The steps two ghosts share, None if there are none.
+fn both_hit(a: (u128, u128), b: (u128, u128)) -> Result<Option<(u128, u128)>, String> {
+    // With one walk's step fixed, check it against the other
+    if a.1 == 0 {
+        return Ok(hits_at(b, a.0).then_some((a.0, 0)));
+    }
+    if b.1 == 0 {
+        return Ok(hits_at(a, b.0).then_some((b.0, 0)));
+    }
+    // Step through the longer period until the shorter one lines up, which
+    // takes at most the shorter period's worth of tries
+    let (long, short) = if a.1 >= b.1 { (a, b) } else { (b, a) };
+    let period = (long.1 / gcd(long.1, short.1))
+        .checked_mul(short.1)
+        .ok_or("Step count overflowed".to_string())?;
+    let mut step = long.0;
+    if step < short.0 {
+        step += (short.0 - step).div_ceil(long.1) * long.1;
+    }
+    for _ in 0..short.1 {
+        if (step - short.0).is_multiple_of(short.1) {
+            return Ok(Some((step, period)));
+        }
+        step = step
+            .checked_add(long.1)
+            .ok_or("Step count overflowed".to_string())?;
+    }
+    Ok(None)
+}
+
+/// Steps until every ghost, starting on each node ending in A, is on a node
+/// ending in Z at once. Each ghost's walk is reduced to the steps it is on a
+/// Z node, and those are intersected across the ghosts, so nothing is assumed
+/// about how the loops line up. On the puzzle input this comes down to the
+/// LCM of the steps each ghost takes alone.
+/// The ghosts walk on `threads` threads.
+fn ghost_steps(parsed_network: &ParsedNetwork, threads: usize) -> Result<u64, String> {
+    let starts: Vec<&str> = parsed_network
+        .nodes
+        .iter()
+        .map(|node| node.name)
+        .filter(|name| name.ends_with('A'))
+        .collect();
+    if starts.is_empty() {
+        return Err("No start nodes ending in A".to_string());
+    }
+    let nodes: HashMap<&str, (&str, &str)> = parsed_network
+        .nodes
+        .iter()
+        .map(|node| (node.name, (node.left, node.right)))
+        .collect();
+
+    let walks = parallel::map_chunks(&starts, threads, |starts| {
+        starts
+            .iter()
+            .map(|start| ghost_hits(&nodes, parsed_network.instructions, start))
+            .collect::<Vec<_>>()
+    });
+
+    let mut shared: Option<Vec<(u128, u128)>> = None;
+    for ghost in walks.into_iter().flatten() {
+        let ghost = ghost?;
+        shared = Some(match shared {
+            None => ghost,
+            Some(shared) => {
+                let mut both = Vec::new();
+                for a in shared.iter() {
+                    for b in ghost.iter() {
+                        both.extend(both_hit(*a, *b)?);
+                    }
+                }
+                both.sort_unstable();
+                both.dedup();
+                both
+            }
+        });
+    }
+    let first = shared
+        .unwrap_or_default()
+        .iter()
+        .map(|(first, _)| *first)
+        .min()
+        .ok_or("The ghosts are never all on Z nodes at once".to_string())?;
+    u64::try_from(first).map_err(|_| "Step count overflowed".to_string())
+}
+
 pub fn part2(input_path: &str) -> Result<u64, String> {
-    let input = fs::read_to_string(input_path).unwrap();
-    println!("{:?}", parse(&input).finish());
-    Ok(0u64)
+    let input = fs::read_to_string(input_path).map_err(|e| format!("File read error: {}", e))?;
+    part2_str(&input)
+}
+
+pub fn part2_str(input: &str) -> Result<u64, String> {
+    part2_parallel(input, 1)
+}
+
+/// Same as `part2_str`, with the ghosts split across `threads` threads.
+pub fn part2_parallel(input: &str, threads: usize) -> Result<u64, String> {
+    let (remain, parsed_network) = parse(input)
+        .finish()
+        .map_err(|e| format!("Parse Error: {}", e))?;
+    if !remain.is_empty() {
+        return Err("Parse Error".to_string());
+    }
+    ghost_steps(&parsed_network, threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Moves every ghost one step at a time until they are all on a Z node,
+    /// giving up after `limit` steps.
+    fn ghost_steps_by_stepping(parsed_network: &ParsedNetwork, limit: u64) -> Option<u64> {
+        let nodes: HashMap<&str, &ParsedNode> = parsed_network
+            .nodes
+            .iter()
+            .map(|node| (node.name, node))
+            .collect();
+        let mut ghosts: Vec<&str> = parsed_network
+            .nodes
+            .iter()
+            .map(|node| node.name)
+            .filter(|name| name.ends_with('A'))
+            .collect();
+        let instructions = parsed_network.instructions;
+        for step in 0..limit {
+            if ghosts.iter().all(|name| name.ends_with('Z')) {
+                return Some(step);
+            }
+            for ghost in ghosts.iter_mut() {
+                let node = nodes[ghost];
+                *ghost = match instructions[step as usize % instructions.len()] {
+                    b'L' => node.left,
+                    _ => node.right,
+                };
+            }
+        }
+        None
+    }
+
+    /// A network of `starts` A nodes followed by one node per `others`
+    /// entry, ending in Z when it is true. Every node's left and right are
+    /// picked from all of them, so walks loop within a few steps.
+    fn network(
+        instructions: &[bool],
+        starts: usize,
+        others: &[bool],
+        edges: &[(usize, usize)],
+    ) -> String {
+        let names: Vec<String> = (0..starts)
+            .map(|i| format!("S{}A", i))
+            .chain(others.iter().enumerate().map(|(i, z)| match z {
+                true => format!("N{}Z", i),
+                false => format!("N{}X", i),
+            }))
+            .collect();
+        let mut lines = vec![
+            instructions
+                .iter()
+                .map(|left| if *left { 'L' } else { 'R' })
+                .collect::<String>(),
+            String::new(),
+        ];
+        for (i, name) in names.iter().enumerate() {
+            let (left, right) = edges[i % edges.len()];
+            lines.push(format!(
+                "{} = ({}, {})",
+                name,
+                names[left % names.len()],
+                names[right % names.len()]
+            ));
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn trace_rejects_bad_instructions() {
+        let path = "src/day8/part1_sample_input.txt";
+        for (instructions, error) in [
+            ("", "No instructions to follow"),
+            ("LRX", "Instruction 'X' is not L or R"),
+        ] {
+            let mut path_trace = PathTrace::new(100, 2, None).unwrap();
+            assert_eq!(
+                trace(path, "AAA", "ZZZ", Some(instructions), &mut path_trace),
+                Err(error.to_string())
+            );
+        }
+        let mut path_trace = PathTrace::new(100, 2, None).unwrap();
+        assert_eq!(trace(path, "AAA", "ZZZ", None, &mut path_trace), Ok(2));
+    }
+
+    #[test]
+    fn ghosts_out_of_step_with_their_first_z() {
+        // 11A reaches 11Z after 1 step, then every 2 steps after that
+        let input = "L\n\n11A = (11Z, 11A)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n\
+                     22A = (22B, 22B)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n22Z = (22Z, 22Z)";
+        assert_eq!(part2_str(input), Ok(3));
+        let never = "L\n\n11A = (11Z, 11A)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n\
+                     22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)";
+        assert!(part2_str(never).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn ghost_steps_match_stepping(
+            instructions in prop::collection::vec(any::<bool>(), 1..5),
+            starts in 1usize..=3,
+            others in prop::collection::vec(any::<bool>(), 1..7),
+            edges in prop::collection::vec((any::<usize>(), any::<usize>()), 1..10),
+        ) {
+            let input = network(&instructions, starts, &others, &edges);
+            let (_, parsed_network) = parse(&input).finish().unwrap();
+            // At most 9 nodes and 4 instructions make loops of at most 36
+            // steps, so three ghosts line up within 36^3 steps or never
+            let stepped = ghost_steps_by_stepping(&parsed_network, 50_000);
+            for threads in [1, 2] {
+                prop_assert_eq!(
+                    ghost_steps(&parsed_network, threads).ok(),
+                    stepped,
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
 }
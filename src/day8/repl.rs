@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use nom::Finish;
+
+use super::{build_network, parse, steps_to_dest, ParsedNetwork};
+use crate::repl::Commands;
+
+/// The parsed network, with each node's (left, right) looked up by name.
+pub struct Session<'a> {
+    network: ParsedNetwork<'a>,
+    nodes: HashMap<&'a str, (&'a str, &'a str)>,
+}
+
+impl<'a> Session<'a> {
+    pub fn new(input: &'a str) -> Result<Session<'a>, String> {
+        let (_, network) = parse(input)
+            .finish()
+            .map_err(|e| format!("Parse Error: {}", e))?;
+        let nodes = network
+            .nodes
+            .iter()
+            .map(|node| (node.name, (node.left, node.right)))
+            .collect();
+        Ok(Session { network, nodes })
+    }
+
+    fn node(&self, name: &str) -> Result<(&'a str, &'a str), String> {
+        self.nodes
+            .get(name)
+            .copied()
+            .ok_or(format!("Node {} not found", name))
+    }
+
+    /// Every node from `start` following each of `instructions` once.
+    fn walk(&self, start: &str, instructions: &[u8]) -> Result<String, String> {
+        self.node(start)?;
+        let mut path = start.to_string();
+        let mut current = start;
+        for instruction in instructions {
+            let (left, right) = self.node(current)?;
+            current = match instruction {
+                b'L' => left,
+                b'R' => right,
+                _ => return Err(format!("Invalid instruction {}", *instruction as char)),
+            };
+            path += &format!(" -{}-> {}", *instruction as char, current);
+        }
+        Ok(path)
+    }
+}
+
+impl Commands for Session<'_> {
+    fn help(&self) -> &'static str {
+        "node <name>                  its left and right nodes\n\
+         walk <start> [instructions]  nodes visited following the instructions once, the input's by default\n\
+         steps <start> [goal]         steps from start to goal (ZZZ by default) repeating the input's instructions\n"
+    }
+
+    fn run(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["node", name] => {
+                let (left, right) = self.node(name)?;
+                Ok(format!("{} = ({}, {})", name, left, right))
+            }
+            ["walk", start] => self.walk(start, self.network.instructions),
+            ["walk", start, instructions] => self.walk(start, instructions.as_bytes()),
+            ["steps", start, goal @ ..] if goal.len() <= 1 => {
+                let goal = goal.first().copied().unwrap_or("ZZZ");
+                let network = build_network(&self.network, start)?;
+                steps_to_dest(
+                    network,
+                    &|name| name == goal,
+                    self.network.instructions,
+                    None,
+                )
+                .map(|steps| steps.to_string())
+            }
+            [command, ..] => Err(format!("Unknown command {}, try help", command)),
+            [] => Ok(String::new()),
+        }
+    }
+}
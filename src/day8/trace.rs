@@ -0,0 +1,147 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Write as _},
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+#[derive(Debug, Clone)]
+pub struct PathStep {
+    pub index: u64,
+    pub node: String,
+    pub instruction: char,
+}
+
+impl Display for PathStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.index, self.node, self.instruction)
+    }
+}
+
+/// Records the steps of a walk, stopping it with an error after `limit`
+/// steps. Only the first and last `tail_len` steps are kept in memory; every
+/// step goes to `file` if set.
+pub struct PathTrace {
+    limit: usize,
+    tail_len: usize,
+    head: Vec<PathStep>,
+    tail: VecDeque<PathStep>,
+    file: Option<BufWriter<File>>,
+    total: u64,
+}
+
+impl PathTrace {
+    pub fn new(
+        limit: usize,
+        tail_len: usize,
+        file_path: Option<&str>,
+    ) -> Result<PathTrace, String> {
+        let file = match file_path {
+            Some(path) => Some(BufWriter::new(
+                File::create(path).map_err(|e| format!("Trace file error: {}", e))?,
+            )),
+            None => None,
+        };
+        Ok(PathTrace {
+            limit,
+            tail_len,
+            head: Vec::new(),
+            tail: VecDeque::new(),
+            file,
+            total: 0,
+        })
+    }
+
+    pub(super) fn record(&mut self, step: PathStep) -> Result<(), String> {
+        if self.total == self.limit as u64 {
+            return Err(format!(
+                "Walk passed the trace limit of {} steps at {}",
+                self.limit, step.node
+            ));
+        }
+        self.total += 1;
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{}", step).map_err(|e| format!("Trace file error: {}", e))?;
+        }
+        if self.head.len() < self.tail_len {
+            self.head.push(step.clone());
+        }
+        if self.tail_len > 0 {
+            if self.tail.len() == self.tail_len {
+                self.tail.pop_front();
+            }
+            self.tail.push_back(step);
+        }
+        Ok(())
+    }
+
+    pub(super) fn finish(&mut self) -> Result<(), String> {
+        match self.file.as_mut() {
+            Some(file) => file.flush().map_err(|e| format!("Trace file error: {}", e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Summary listing the first and last `tail_len` steps of the walk.
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        let show = self.tail_len as u64;
+        writeln!(out, "{} steps recorded", self.total).unwrap();
+        for step in self.head.iter().take(self.tail_len) {
+            writeln!(out, "{}", step).unwrap();
+        }
+        if self.total > 2 * show {
+            writeln!(out, "...").unwrap();
+        }
+        for step in self.tail.iter().filter(|s| s.index >= show) {
+            writeln!(out, "{}", step).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk(trace: &mut PathTrace, steps: u64) -> Result<(), String> {
+        for index in 0..steps {
+            trace.record(PathStep {
+                index,
+                node: format!("N{}", index),
+                instruction: 'L',
+            })?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn summary_shows_both_ends() {
+        let mut trace = PathTrace::new(100, 2, None).unwrap();
+        walk(&mut trace, 7).unwrap();
+        assert_eq!(
+            trace.summary(),
+            "7 steps recorded\n0 N0 L\n1 N1 L\n...\n5 N5 L\n6 N6 L\n"
+        );
+
+        // Short walks show each step once
+        let mut trace = PathTrace::new(100, 2, None).unwrap();
+        walk(&mut trace, 3).unwrap();
+        assert_eq!(
+            trace.summary(),
+            "3 steps recorded\n0 N0 L\n1 N1 L\n2 N2 L\n"
+        );
+    }
+
+    #[test]
+    fn limit_stops_the_walk() {
+        let mut trace = PathTrace::new(5, 2, None).unwrap();
+        assert!(walk(&mut trace, 5).is_ok());
+        let mut trace = PathTrace::new(5, 2, None).unwrap();
+        assert_eq!(
+            walk(&mut trace, 6),
+            Err("Walk passed the trace limit of 5 steps at N5".to_string())
+        );
+        assert!(trace.summary().starts_with("5 steps recorded"));
+    }
+}
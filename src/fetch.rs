@@ -0,0 +1,183 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2023";
+
+/// Where fetched inputs are kept: `$AOC_CACHE_DIR`, or `adventofcode2023` in
+/// the user's cache directory. Outside the repository, so inputs are never
+/// committed.
+pub fn cache_dir() -> Result<PathBuf, String> {
+    match std::env::var_os("AOC_CACHE_DIR") {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::cache_dir()
+            .map(|dir| dir.join("adventofcode2023"))
+            .ok_or("No cache directory for this user, set AOC_CACHE_DIR".to_string()),
+    }
+}
+
+fn cached_path(cache: &Path, day: &str) -> PathBuf {
+    cache.join(day).join("input.txt")
+}
+
+/// The real input for `day`: the fetched one if there is one, otherwise
+/// `src/<day>/input.txt`.
+pub fn input_path(day: &str) -> String {
+    cache_dir()
+        .map(|cache| cached_path(&cache, day))
+        .ok()
+        .filter(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or(format!("src/{}/input.txt", day))
+}
+
+/// The puzzle number in `dayN`.
+pub fn day_number(day: &str) -> Result<u32, String> {
+    day.strip_prefix("day")
+        .and_then(|n| n.parse().ok())
+        .filter(|n| (1..=25).contains(n))
+        .ok_or(format!("Bad day {}, expected day1 to day25", day))
+}
+
+/// Talks to the puzzle site as the user whose session cookie it holds.
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Client {
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.trim().to_string(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .user_agent("github.com/nickgarvey/adventofcode2023 by garvey.nick@gmail.com")
+                .build(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Sends `request` with the session cookie, giving back the body of a
+    /// successful response. The token never appears in errors.
+    fn send(
+        &self,
+        request: ureq::Request,
+        form: Option<&[(&str, &str)]>,
+    ) -> Result<String, String> {
+        let url = request.url().to_string();
+        let request = request.set("Cookie", &format!("session={}", self.session));
+        let response = match form {
+            Some(form) => request.send_form(form),
+            None => request.call(),
+        };
+        match response {
+            Ok(response) => response
+                .into_string()
+                .map_err(|e| format!("Failed to read response from {}: {}", url, e)),
+            Err(ureq::Error::Status(code @ (400 | 401 | 403), _)) => Err(format!(
+                "{} rejected the session token ({}), it may have expired",
+                url, code
+            )),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(format!("{} not found, is the puzzle unlocked?", url))
+            }
+            Err(ureq::Error::Status(code, response)) => Err(format!(
+                "{} failed: {} {}",
+                url,
+                code,
+                response.status_text()
+            )),
+            Err(e) => Err(format!("Request to {} failed: {}", url, e)),
+        }
+    }
+
+    pub fn get(&self, path: &str) -> Result<String, String> {
+        self.send(self.agent.get(&self.url(path)), None)
+    }
+
+    pub fn post_form(&self, path: &str, form: &[(&str, &str)]) -> Result<String, String> {
+        self.send(self.agent.post(&self.url(path)), Some(form))
+    }
+}
+
+/// Downloads `day`'s input into `cache` unless it is already there, or
+/// always with `force`. Returns where the input is.
+pub fn fetch(client: &Client, cache: &Path, day: &str, force: bool) -> Result<PathBuf, String> {
+    let path = cached_path(cache, day);
+    if path.exists() && !force {
+        return Ok(path);
+    }
+
+    let input = client.get(&format!("/day/{}/input", day_number(day)?))?;
+    if input.trim().is_empty() {
+        return Err(format!("Got an empty input for {}", day));
+    }
+    let dir = path.parent().unwrap();
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    // Written whole then renamed, so an interrupted fetch leaves no input
+    let partial = path.with_extension("txt.part");
+    fs::write(&partial, input)
+        .and_then(|_| fs::rename(&partial, &path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::StubServer;
+
+    fn temp_cache(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("fetch_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn fetch_downloads_once_then_uses_cache() {
+        let server = StubServer::start(&[(200, "1abc2\n"), (200, "3def4\n")]);
+        let client = Client::new(&format!("{}/2023/", server.url()), "token\n");
+        let cache = temp_cache("once");
+
+        let path = fetch(&client, &cache, "day1", false).unwrap();
+        assert_eq!(path, cache.join("day1").join("input.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1abc2\n");
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/2023/day/1/input");
+        assert_eq!(requests[0].header("Cookie"), Some("session=token"));
+        assert_eq!(requests[0].body, "");
+
+        fetch(&client, &cache, "day1", false).unwrap();
+        assert_eq!(server.requests().len(), 1);
+        fetch(&client, &cache, "day1", true).unwrap();
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3def4\n");
+        fs::remove_dir_all(&cache).unwrap();
+    }
+
+    #[test]
+    fn fetch_errors_leave_no_input() {
+        let server = StubServer::start(&[(404, "Not yet"), (400, "Log in"), (200, "")]);
+        let client = Client::new(server.url(), "token");
+        let cache = temp_cache("errors");
+
+        let not_found = fetch(&client, &cache, "day9", false).unwrap_err();
+        assert!(not_found.contains("not found"), "{}", not_found);
+        let rejected = fetch(&client, &cache, "day9", false).unwrap_err();
+        assert!(rejected.contains("session token"), "{}", rejected);
+        assert!(!rejected.contains("token\n") && !rejected.contains("=token"));
+        assert!(fetch(&client, &cache, "day9", false).is_err());
+        assert!(!cached_path(&cache, "day9").exists());
+        assert!(fetch(&client, &cache, "day26", false).is_err());
+        assert_eq!(server.requests().len(), 3);
+    }
+}
@@ -0,0 +1,32 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["line_len", "digit_rate", "word_rate"];
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// `size` calibration lines of lowercase letters with digits and digit words
+/// mixed in. Lines can end up with no digits at all.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let line_len: usize = knobs.get("line_len", 40)?;
+    let digit_rate: f64 = knobs.get("digit_rate", 0.05)?;
+    let word_rate: f64 = knobs.get("word_rate", 0.05)?;
+
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let target_len = rng.range(1, line_len.max(1) as u64) as usize;
+        let mut line = String::new();
+        while line.len() < target_len {
+            if rng.chance(word_rate) {
+                line.push_str(WORDS[rng.below(WORDS.len())]);
+            } else if rng.chance(digit_rate) {
+                line.push((b'0' + rng.range(1, 9) as u8) as char);
+            } else {
+                line.push((b'a' + rng.below(26) as u8) as char);
+            }
+        }
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,41 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["colors", "max_count", "draws"];
+
+const COLORS: [&str; 8] = [
+    "red", "green", "blue", "yellow", "purple", "orange", "white", "black",
+];
+
+/// `size` games numbered from 1, each with up to `draws` draws of up to
+/// `colors` distinct colors.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let num_colors: usize = knobs.get("colors", 3)?;
+    let max_count: u64 = knobs.get("max_count", 20)?;
+    let max_draws: usize = knobs.get("draws", 6)?;
+    if num_colors == 0 || num_colors > COLORS.len() {
+        return Err(format!("colors must be 1 to {}", COLORS.len()));
+    }
+    let colors = &COLORS[..num_colors];
+
+    let mut lines = Vec::with_capacity(size);
+    for game_id in 1..=size {
+        let num_draws = rng.range(1, max_draws.max(1) as u64);
+        let draws: Vec<String> = (0..num_draws)
+            .map(|_| {
+                // A random non-empty subset of the colors, in a random order
+                let mut shown: Vec<&str> = colors.to_vec();
+                for idx in (1..shown.len()).rev() {
+                    shown.swap(idx, rng.below(idx + 1));
+                }
+                shown.truncate(rng.range(1, colors.len() as u64) as usize);
+                shown
+                    .iter()
+                    .map(|color| format!("{} {}", rng.range(1, max_count.max(1)), color))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+        lines.push(format!("Game {}: {}", game_id, draws.join("; ")));
+    }
+    Ok(lines.join("\n"))
+}
@@ -0,0 +1,43 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["width", "number_rate", "symbol_rate", "gear_rate"];
+
+const SYMBOLS: &[u8] = b"#$%&+-/=@";
+
+/// A `width` by `size` schematic. Each cell starts a number with
+/// `number_rate`, else is a symbol with `symbol_rate` (a `*` with
+/// `gear_rate` of those), else a '.'.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let width: usize = knobs.get("width", 140)?;
+    let number_rate: f64 = knobs.get("number_rate", 0.05)?;
+    let symbol_rate: f64 = knobs.get("symbol_rate", 0.03)?;
+    let gear_rate: f64 = knobs.get("gear_rate", 0.3)?;
+    if width == 0 {
+        return Err("width must be at least 1".to_string());
+    }
+
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let mut line: Vec<u8> = Vec::with_capacity(width);
+        while line.len() < width {
+            if rng.chance(number_rate) {
+                let num = rng.range(1, 999).to_string();
+                line.extend(num.bytes().take(width - line.len()));
+                // Keep numbers apart so each one is the number generated
+                if line.len() < width {
+                    line.push(b'.');
+                }
+            } else if rng.chance(symbol_rate) {
+                line.push(if rng.chance(gear_rate) {
+                    b'*'
+                } else {
+                    *rng.choose(SYMBOLS)
+                });
+            } else {
+                line.push(b'.');
+            }
+        }
+        lines.push(String::from_utf8(line).unwrap());
+    }
+    Ok(lines.join("\n"))
+}
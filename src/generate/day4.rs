@@ -0,0 +1,80 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["winning", "numbers", "max_num"];
+
+/// Distinct numbers from 1 to `max` in random order.
+fn distinct(rng: &mut Rng, count: usize, max: usize) -> Vec<usize> {
+    let mut pool: Vec<usize> = (1..=max).collect();
+    for idx in 0..count {
+        let pick = idx + rng.below(pool.len() - idx);
+        pool.swap(idx, pick);
+    }
+    pool.truncate(count);
+    pool
+}
+
+/// `size` scratchcards, each with `winning` winning numbers and `numbers`
+/// player numbers drawn from 1 to `max_num`, padded like the real input. As
+/// in the puzzle, no card wins copies past the end of the table. Copies grow
+/// exponentially, so cards win fewer numbers where more would overflow part2's
+/// u128 count.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let winning: usize = knobs.get("winning", 10)?;
+    let numbers: usize = knobs.get("numbers", 25)?;
+    let max_num: usize = knobs.get("max_num", 99)?;
+    if winning == 0 || numbers == 0 || winning + numbers > max_num {
+        return Err("winning and numbers must be at least 1 and add up to max_num".to_string());
+    }
+    // A card is worth 2^(winners - 1) in part1, added up in a u128
+    if winning.min(numbers) > 64 {
+        return Err("Cards can win at most 64 numbers, lower winning or numbers".to_string());
+    }
+
+    let num_width = max_num.to_string().len();
+    let id_width = size.to_string().len();
+    let format_nums = |nums: &[usize]| {
+        nums.iter()
+            .map(|num| format!("{:>width$}", num, width = num_width))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    // Copies of each card, each kept small enough that their total fits
+    let mut copies: Vec<u128> = vec![1; size];
+    let max_copies = u128::MAX / size as u128;
+    let mut lines = Vec::with_capacity(size);
+    for card_id in 1..=size {
+        // The first `winning` are the winning numbers, the rest never win
+        let pool = distinct(rng, max_num, max_num);
+        let (winning_nums, losing_nums) = pool.split_at(winning);
+        let natural_winners = distinct(rng, numbers, max_num)
+            .iter()
+            .filter(|num| winning_nums.contains(num))
+            .count();
+        let won = copies[card_id - 1];
+        let num_winners = copies[card_id..]
+            .iter()
+            .take(natural_winners)
+            .take_while(|count| count.checked_add(won).is_some_and(|c| c <= max_copies))
+            .count();
+        for count in copies[card_id..card_id + num_winners].iter_mut() {
+            *count += won;
+        }
+        let mut player_nums: Vec<usize> = winning_nums[..num_winners]
+            .iter()
+            .chain(&losing_nums[..numbers - num_winners])
+            .copied()
+            .collect();
+        for idx in (1..player_nums.len()).rev() {
+            player_nums.swap(idx, rng.below(idx + 1));
+        }
+        lines.push(format!(
+            "Card {:>width$}: {} | {}",
+            card_id,
+            format_nums(winning_nums),
+            format_nums(&player_nums),
+            width = id_width
+        ));
+    }
+    Ok(lines.join("\n"))
+}
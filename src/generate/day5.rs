@@ -0,0 +1,48 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["ranges", "max_value", "max_len", "seed_len"];
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// An almanac with `size` seed pairs (start, length), so it works for both
+/// parts, and a map between each category with `ranges` ranges. Seed range
+/// lengths stay under `seed_len` so part2's per-seed walk stays feasible.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let ranges: usize = knobs.get("ranges", 10)?;
+    let max_value: u64 = knobs.get("max_value", 1 << 32)?;
+    let max_len: u64 = knobs.get("max_len", 1 << 28)?;
+    let seed_len: u64 = knobs.get("seed_len", 1000)?;
+    if max_value < 2 || max_len == 0 || seed_len == 0 {
+        return Err("max_value must be at least 2, max_len and seed_len at least 1".to_string());
+    }
+
+    let seeds: Vec<String> = (0..size)
+        .flat_map(|_| {
+            let start = rng.range(0, max_value - 1);
+            let len = rng.range(1, seed_len);
+            [start.to_string(), len.to_string()]
+        })
+        .collect();
+
+    let mut sections = vec![format!("seeds: {}", seeds.join(" "))];
+    for pair in CATEGORIES.windows(2) {
+        let mut section = format!("{}-to-{} map:", pair[0], pair[1]);
+        for _ in 0..ranges {
+            let length = rng.range(1, max_len.min(max_value));
+            let destination = rng.range(0, max_value - length);
+            let source = rng.range(0, max_value - length);
+            section.push_str(&format!("\n{} {} {}", destination, source, length));
+        }
+        sections.push(section);
+    }
+    Ok(sections.join("\n\n"))
+}
@@ -0,0 +1,53 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["max_time"];
+
+/// `size` races, each with a time up to `max_time` and a record distance that
+/// can be beaten. Part2 joins every race's digits into one u64, so only as
+/// many races as surely fit are allowed.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let max_time: u64 = knobs.get("max_time", 100)?;
+    if !(2..=u32::MAX as u64).contains(&max_time) {
+        return Err(format!("max_time must be 2 to {}", u32::MAX));
+    }
+    // Any 19 digit number fits in a u64. Records are the longest numbers, and
+    // part1's product of ways to win is shorter than the joined times
+    let record_digits = ((max_time / 2) * (max_time - max_time / 2))
+        .to_string()
+        .len();
+    let max_races = 19 / record_digits;
+    if size > max_races {
+        return Err(format!(
+            "At most {} races with max_time {} fit in part2's u64",
+            max_races, max_time
+        ));
+    }
+
+    let mut times = Vec::with_capacity(size);
+    let mut distances = Vec::with_capacity(size);
+    for _ in 0..size {
+        let time = rng.range(2, max_time);
+        let best = (time / 2) * (time - time / 2);
+        times.push(time.to_string());
+        distances.push(rng.range(0, best - 1).to_string());
+    }
+    // Right-align each race's column, as in the real input
+    let width = times
+        .iter()
+        .chain(distances.iter())
+        .map(|s| s.len())
+        .max()
+        .unwrap_or(1);
+    let row = |values: &[String]| {
+        values
+            .iter()
+            .map(|v| format!("{:>width$}", v, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    Ok(format!(
+        "Time:      {}\nDistance:  {}",
+        row(&times),
+        row(&distances)
+    ))
+}
@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["max_bid", "joker_rate"];
+
+const CARDS: &[u8] = b"23456789TQKA";
+
+/// `size` distinct hands, as the solvers reject ties, each card a joker with
+/// `joker_rate`.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let max_bid: u64 = knobs.get("max_bid", 1000)?;
+    let joker_rate: f64 = knobs.get("joker_rate", 1.0 / 13.0)?;
+    if !(0.0..1.0).contains(&joker_rate) {
+        return Err("joker_rate must be from 0 up to 1".to_string());
+    }
+    // 13^5 possible hands, or 12^5 without jokers
+    let distinct_hands = if joker_rate > 0.0 { 371_293 } else { 248_832 };
+    if size > distinct_hands {
+        return Err(format!("There are only {} distinct hands", distinct_hands));
+    }
+
+    let mut seen = HashSet::new();
+    let mut lines = Vec::with_capacity(size);
+    while lines.len() < size {
+        let hand: String = (0..5)
+            .map(|_| match rng.chance(joker_rate) {
+                true => 'J',
+                false => *rng.choose(CARDS) as char,
+            })
+            .collect();
+        if seen.insert(hand.clone()) {
+            lines.push(format!("{} {}", hand, rng.range(1, max_bid.max(1))));
+        }
+    }
+    Ok(lines.join("\n"))
+}
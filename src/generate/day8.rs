@@ -0,0 +1,64 @@
+use super::{Knobs, Rng};
+
+pub const KNOBS: &[&str] = &["instructions", "path"];
+
+fn node_name(idx: usize) -> String {
+    let letter = |n: usize| (b'A' + (n % 26) as u8) as char;
+    [letter(idx / 676), letter(idx / 26), letter(idx)]
+        .iter()
+        .collect()
+}
+
+/// A network of `size` nodes, at least AAA and ZZZ, with `instructions` L/R
+/// steps. Following the instructions from AAA reaches ZZZ after `path` steps
+/// through fresh nodes; all other edges are random.
+pub fn generate(rng: &mut Rng, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let num_instructions: usize = knobs.get("instructions", 300)?;
+    if !(2..=26 * 26 * 26).contains(&size) {
+        return Err("size must be from 2 to 17576".to_string());
+    }
+    let path: usize = knobs.get("path", (size - 1).min(100))?;
+    if path == 0 || path > size - 1 {
+        return Err(format!("path must be from 1 to {}", size - 1));
+    }
+    if num_instructions == 0 {
+        return Err("instructions must be at least 1".to_string());
+    }
+
+    let instructions: Vec<u8> = (0..num_instructions).map(|_| *rng.choose(b"LR")).collect();
+
+    // AAA first and ZZZ last, the rest shuffled in between
+    let aaa = 0;
+    let zzz = 26 * 26 * 26 - 1;
+    let mut others: Vec<usize> = (1..zzz).collect();
+    for idx in 0..size - 2 {
+        let pick = idx + rng.below(others.len() - idx);
+        others.swap(idx, pick);
+    }
+    let mut names = vec![aaa];
+    names.extend_from_slice(&others[..size - 2]);
+    names.push(zzz);
+
+    // (left, right) as indexes into names, None until chosen
+    let mut edges: Vec<(Option<usize>, Option<usize>)> = vec![(None, None); size];
+    for step in 0..path {
+        let next = if step + 1 == path { size - 1 } else { step + 1 };
+        match instructions[step % instructions.len()] {
+            b'L' => edges[step].0 = Some(next),
+            _ => edges[step].1 = Some(next),
+        }
+    }
+
+    let mut lines = vec![String::from_utf8(instructions).unwrap(), String::new()];
+    for (idx, (left, right)) in edges.into_iter().enumerate() {
+        let left = left.unwrap_or_else(|| rng.below(size));
+        let right = right.unwrap_or_else(|| rng.below(size));
+        lines.push(format!(
+            "{} = ({}, {})",
+            node_name(names[idx]),
+            node_name(names[left]),
+            node_name(names[right])
+        ));
+    }
+    Ok(lines.join("\n"))
+}
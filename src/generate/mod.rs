@@ -0,0 +1,164 @@
+use std::{collections::HashMap, str::FromStr};
+
+mod day1;
+mod day2;
+mod day3;
+mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+
+/// SplitMix64. Small and good enough for puzzle inputs, and the same seed
+/// always gives the same input.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `low..=high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// Uniform in `0..n`, which must not be empty.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Rng::below(0) has nothing to pick from");
+        self.range(0, n as u64 - 1) as usize
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// Shape settings given as `key=value` pairs, each generator reading the
+/// ones it knows with its own defaults.
+pub struct Knobs {
+    values: HashMap<String, String>,
+}
+
+impl Knobs {
+    pub fn parse(pairs: &[String]) -> Result<Knobs, String> {
+        let mut values = HashMap::new();
+        for pair in pairs {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or(format!("Bad knob {:?}, expected key=value", pair))?;
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+        Ok(Knobs { values })
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        match self.values.get(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|e| format!("Bad value {:?} for knob {}: {}", value, key, e)),
+            None => Ok(default),
+        }
+    }
+
+    fn check(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .values
+            .keys()
+            .find(|key| !known.contains(&key.as_str()))
+        {
+            Some(key) => Err(format!(
+                "Unknown knob {}, expected one of: {}",
+                key,
+                known.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+type Generator = fn(&mut Rng, usize, &Knobs) -> Result<String, String>;
+
+/// Generates an input for `day` that its parser accepts. `size` is the main
+/// dimension (lines, games, cards, ...), the rest of the shape comes from
+/// `knobs`. Inputs have no trailing newline, like the real ones.
+pub fn generate(day: &str, seed: u64, size: usize, knobs: &Knobs) -> Result<String, String> {
+    let mut rng = Rng::new(seed);
+    let (known, generator): (&[&str], Generator) = match day {
+        "day1" => (day1::KNOBS, day1::generate),
+        "day2" => (day2::KNOBS, day2::generate),
+        "day3" => (day3::KNOBS, day3::generate),
+        "day4" => (day4::KNOBS, day4::generate),
+        "day5" => (day5::KNOBS, day5::generate),
+        "day6" => (day6::KNOBS, day6::generate),
+        "day7" => (day7::KNOBS, day7::generate),
+        "day8" => (day8::KNOBS, day8::generate),
+        _ => return Err(format!("No generator for {}", day)),
+    };
+    knobs.check(known)?;
+    if size == 0 {
+        return Err("Size must be at least 1".to_string());
+    }
+    generator(&mut rng, size, knobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adventofcode2023::{day4, day6};
+
+    #[test]
+    #[should_panic(expected = "nothing to pick from")]
+    fn below_zero_panics() {
+        Rng::new(0).below(0);
+    }
+
+    #[test]
+    fn below_one_is_zero() {
+        let mut rng = Rng::new(7);
+        assert!((0..100).all(|_| rng.below(1) == 0));
+    }
+
+    #[test]
+    fn large_inputs_stay_solvable() {
+        let knobs = Knobs::parse(&[]).unwrap();
+        for seed in 0..3 {
+            let input = generate("day4", seed, 2000, &knobs).unwrap();
+            assert!(day4::part1_str(&input).is_ok());
+            assert!(day4::part2_str(&input, day4::PastEnd::Error).is_ok());
+            let input = generate("day6", seed, 4, &knobs).unwrap();
+            assert!(day6::part1_str(&input).is_ok());
+            assert!(day6::part2_str(&input).is_ok());
+        }
+        let many_winners = Knobs::parse(&[
+            "winning=90".to_string(),
+            "numbers=90".to_string(),
+            "max_num=200".to_string(),
+        ])
+        .unwrap();
+        assert!(generate("day4", 0, 10, &many_winners).is_err());
+        assert!(generate("day6", 0, 5, &knobs).is_err());
+        let short = Knobs::parse(&["max_time=9".to_string()]).unwrap();
+        assert!(generate("day6", 0, 9, &short).is_ok());
+    }
+}
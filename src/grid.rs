@@ -0,0 +1,299 @@
+use std::ops::{Index, IndexMut};
+
+/// A rectangular grid stored row by row. Positions are (x, y) with x the
+/// column and y the row, both from the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const OFFSETS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Result<Grid<T>, String> {
+        if width * height != cells.len() {
+            return Err(format!(
+                "{} cells do not fill a {}x{} grid",
+                cells.len(),
+                width,
+                height
+            ));
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if self.in_bounds(x, y) {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if self.in_bounds(x, y) {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    fn offset_positions<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(*dx)?;
+            let ny = y.checked_add_signed(*dy)?;
+            self.in_bounds(nx, ny).then_some((nx, ny))
+        })
+    }
+
+    /// In-bounds positions above, left, right and below (x, y).
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offset_positions(x, y, &OFFSETS_4)
+    }
+
+    /// In-bounds positions around (x, y), diagonals included.
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offset_positions(x, y, &OFFSETS_8)
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on 0, and a zero width grid has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        let skip = if x < width { x } else { self.cells.len() };
+        self.cells.iter().skip(skip).step_by(width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Mirrors the grid along its main diagonal, so rows become columns.
+    pub fn transpose(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self.columns().flat_map(|col| col.cloned()).collect(),
+        }
+    }
+
+    /// Quarter turn clockwise: the left column becomes the top row.
+    pub fn rotate_clockwise(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: self
+                .columns()
+                .flat_map(|col| col.cloned().collect::<Vec<T>>().into_iter().rev())
+                .collect(),
+        }
+    }
+
+    /// Quarter turn counterclockwise: the right column becomes the top row.
+    pub fn rotate_counterclockwise(&self) -> Grid<T> {
+        Grid {
+            width: self.height,
+            height: self.width,
+            cells: (0..self.width)
+                .rev()
+                .flat_map(|x| self.column(x).cloned())
+                .collect(),
+        }
+    }
+}
+
+impl Grid<u8> {
+    /// Builds a grid of bytes, one row per line. A single trailing newline is
+    /// ignored and every line must be the same length.
+    pub fn parse(input: &[u8]) -> Result<Grid<u8>, String> {
+        let input = input.strip_suffix(b"\n").unwrap_or(input);
+        if input.is_empty() {
+            return Grid::new(0, 0, Vec::new());
+        }
+
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for line in input.split(|c| *c == b'\n') {
+            let expected = *width.get_or_insert(line.len());
+            if line.len() != expected {
+                return Err(format!(
+                    "line {} is {} bytes long, but line 1 is {}",
+                    height + 1,
+                    line.len(),
+                    expected
+                ));
+            }
+            cells.extend_from_slice(line);
+            height += 1;
+        }
+        Grid::new(width.unwrap_or(0), height, cells)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside the grid", x, y))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) is outside the grid", x, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &str) -> Grid<u8> {
+        Grid::parse(rows.as_bytes()).unwrap()
+    }
+
+    fn columns(grid: &Grid<u8>) -> Vec<Vec<u8>> {
+        grid.columns().map(|col| col.copied().collect()).collect()
+    }
+
+    #[test]
+    fn parse_checks_line_lengths() {
+        assert_eq!(grid("ab\ncd\n").row(1), Some(&b"cd"[..]));
+        assert_eq!(grid("").width(), 0);
+        assert_eq!(grid("").rows().count(), 0);
+        assert_eq!(
+            Grid::parse(b"ab\nc").unwrap_err(),
+            "line 2 is 1 bytes long, but line 1 is 2"
+        );
+        assert!(Grid::new(2, 2, vec![0; 3]).is_err());
+    }
+
+    #[test]
+    fn single_row_and_column() {
+        let row = grid("abc");
+        assert_eq!(
+            columns(&row),
+            vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]
+        );
+        assert_eq!(row.column(3).count(), 0);
+        assert_eq!(row.row(1), None);
+        let column = grid("a\nb\nc");
+        assert_eq!(row.transpose(), column);
+        assert_eq!(column.transpose(), row);
+        assert_eq!(row.rotate_clockwise(), column);
+        assert_eq!(row.rotate_counterclockwise(), grid("c\nb\na"));
+        assert_eq!(column.rotate_clockwise(), grid("cba"));
+        assert_eq!(column.rotate_counterclockwise(), row);
+        assert_eq!(row.neighbours4(0, 0).collect::<Vec<_>>(), vec![(1, 0)]);
+        assert_eq!(
+            column.neighbours8(0, 1).collect::<Vec<_>>(),
+            vec![(0, 0), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn transpose_and_rotate() {
+        let g = grid("abc\ndef");
+        assert_eq!(g.transpose(), grid("ad\nbe\ncf"));
+        assert_eq!(g.rotate_clockwise(), grid("da\neb\nfc"));
+        assert_eq!(g.rotate_counterclockwise(), grid("cf\nbe\nad"));
+        assert_eq!(g.transpose().transpose(), g);
+        let around = g
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise();
+        assert_eq!(around, g);
+        assert_eq!(grid("").transpose(), grid(""));
+    }
+
+    #[test]
+    fn neighbours_stay_in_bounds() {
+        let g = grid("abc\ndef\nghi");
+        assert_eq!(
+            g.neighbours4(0, 0).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            g.neighbours4(2, 2).collect::<Vec<_>>(),
+            vec![(2, 1), (1, 2)]
+        );
+        assert_eq!(
+            g.neighbours8(2, 0).collect::<Vec<_>>(),
+            vec![(1, 0), (1, 1), (2, 1)]
+        );
+        assert_eq!(g.neighbours8(1, 1).count(), 8);
+        assert_eq!(g.neighbours4(1, 1).count(), 4);
+        assert_eq!(g.neighbours8(5, 5).count(), 0);
+        assert_eq!(grid("a").neighbours8(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn indexing_corners() {
+        let mut g = grid("ab\ncd");
+        assert_eq!(g[(1, 1)], b'd');
+        g[(0, 1)] = b'x';
+        assert_eq!(g.get(0, 1), Some(&b'x'));
+        assert_eq!(g.get(2, 0), None);
+        assert_eq!(g.get_mut(0, 2), None);
+        assert_eq!(
+            g.positions().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+    }
+}
@@ -0,0 +1,16 @@
+//! The solutions, one module per day, and the `grid` the grid puzzles share.
+//! `main.rs` runs them from the command line, and the fuzz targets in `fuzz/`
+//! call them directly.
+
+mod aho_corasick;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod grid;
+mod parallel;
+pub mod repl;
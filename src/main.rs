@@ -1,39 +1,357 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use std::{
+    fs::File,
+    io::{BufReader, IsTerminal},
+    time::{Duration, SystemTime},
+};
 
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
+use adventofcode2023::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+mod answers;
+mod fetch;
+mod generate;
+#[cfg(test)]
+mod stub_server;
+mod submit;
+mod watch;
 
 #[derive(Parser)]
 #[command(name = "nickgarvey Advent of Code 2023")]
 #[command(author = "Nick Garvey <garvey.nick@gmail.com>")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
-    day: String,
-    part: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(required = true)]
+    day: Option<String>,
+    #[arg(required = true)]
+    part: Option<String>,
 
     #[arg(short = 's', long)]
     use_sample_input: bool,
+
+    /// List every day1 line with the tokens that produced its value
+    #[arg(short = 'e', long)]
+    explain: bool,
+
+    /// Built-in vocabulary name (digits, english, french, german, spanish, roman) or a
+    /// word=value file for day1 calibrate
+    #[arg(long, default_value = "english")]
+    vocabulary: String,
+
+    /// How day1 calibrate joins the first and last values: concat, sum, product
+    #[arg(long, default_value = "concat")]
+    combine: String,
+
+    /// Bag contents for day2 as color=count,... or a file holding that list
+    #[arg(long)]
+    bag: Option<String>,
+
+    /// Day3 rules: a preset (part1, part2) and/or symbols=, gears=,
+    /// neighbours=, combine= entries, comma separated
+    #[arg(long, default_value = "part1")]
+    rules: String,
+
+    /// Never use ANSI colors when rendering
+    #[arg(long)]
+    plain: bool,
+
+    /// Let day4 cards that win past the last card copy only the cards that exist
+    #[arg(long)]
+    clamp: bool,
+
+    /// Start node for day8 walks
+    #[arg(long, default_value = "AAA")]
+    start: String,
+
+    /// Goal node for day8 walks
+    #[arg(long, default_value = "ZZZ")]
+    goal: String,
+
+    /// Highlight the walk from --start in day8 dot output
+    #[arg(long)]
+    highlight: bool,
+
+    /// Instruction string to use instead of the one in the day8 input
+    #[arg(long)]
+    instructions: Option<String>,
+
+    /// Number of steps a day8 trace walks before giving up
+    #[arg(long, default_value_t = 100_000)]
+    trace_limit: usize,
+
+    /// File to write every step of a day8 trace to
+    #[arg(long)]
+    trace_file: Option<String>,
+
+    /// Number of steps to print from each end of a day8 trace
+    #[arg(long, default_value_t = 10)]
+    trace_show: usize,
+
+    /// Read the input a line at a time instead of all at once (day1, day2,
+    /// day4 and day7)
+    #[arg(long)]
+    stream: bool,
+
+    /// Split the work across this many threads (day1, day2, day5 and day8),
+    /// 0 for one per core
+    #[arg(long, default_value_t = 1, conflicts_with = "stream")]
+    threads: usize,
+}
+
+/// Runs a day's part on `path` with the puzzle's own settings.
+fn solve(day: &str, part: &str, path: &str) -> Result<String, String> {
+    match (day, part) {
+        ("day1", "part1") => day1::part1(path).map(|v| v.to_string()),
+        ("day1", "part2") => day1::part2(path).map(|v| v.to_string()),
+        ("day2", "part1") => day2::part1(path, &day2::Bag::default()).map(|v| v.to_string()),
+        ("day2", "part2") => day2::part2(path).map(|v| v.to_string()),
+        ("day3", "part1") => day3::part1(path).map(|v| v.to_string()),
+        ("day3", "part2") => day3::part2(path).map(|v| v.to_string()),
+        ("day4", "part1") => day4::part1(path).map(|v| v.to_string()),
+        ("day4", "part2") => day4::part2(path, day4::PastEnd::Error).map(|v| v.to_string()),
+        ("day5", "part1") => day5::part1(path).map(|v| v.to_string()),
+        ("day5", "part2") => day5::part2(path).map(|v| v.to_string()),
+        ("day6", "part1") => day6::part1(path).map(|v| v.to_string()),
+        ("day6", "part2") => day6::part2(path).map(|v| v.to_string()),
+        ("day7", "part1") => day7::part1(path).map(|v| v.to_string()),
+        ("day7", "part2") => day7::part2(path).map(|v| v.to_string()),
+        ("day8", "part1") => day8::part1(path).map(|v| v.to_string()),
+        ("day8", "part2") => day8::part2(path).map(|v| v.to_string()),
+        _ => Err("Bad day/part number. Args should be: day1 part1".to_string()),
+    }
+}
+
+/// Each day's modes beyond part1 and part2, with the part they extend.
+const MODES: &[(&str, &str, &str)] = &[
+    ("day1", "calibrate", "part2"),
+    ("day2", "report", "part1"),
+    ("day3", "rules", "part1"),
+    ("day3", "render", "part1"),
+    ("day4", "trace", "part2"),
+    ("day5", "brute", "part2"),
+    ("day8", "dot", "part1"),
+    ("day8", "trace", "part1"),
+];
+
+/// The sample input for `part`, or for the part a mode extends.
+fn sample_path(day: &str, part: &str) -> String {
+    let sample_part = match MODES.iter().find(|(d, mode, _)| *d == day && *mode == part) {
+        Some((_, _, extends)) => extends,
+        None if part == "part2" => "part2",
+        None => "part1",
+    };
+    format!("src/{}/{}_sample_input.txt", day, sample_part)
+}
+
+fn open(path: &str) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("File read error: {}", e))
+}
+
+/// Like `solve`, but streaming the input for the days that can.
+fn solve_stream(day: &str, part: &str, path: &str) -> Result<String, String> {
+    let reader = open(path)?;
+    match (day, part) {
+        ("day1", "part1") => day1::part1_reader(reader).map(|v| v.to_string()),
+        ("day1", "part2") => day1::part2_reader(reader).map(|v| v.to_string()),
+        ("day2", "part1") => {
+            day2::part1_reader(reader, &day2::Bag::default()).map(|v| v.to_string())
+        }
+        ("day2", "part2") => day2::part2_reader(reader).map(|v| v.to_string()),
+        ("day4", "part1") => day4::part1_reader(reader).map(|v| v.to_string()),
+        ("day4", "part2") => {
+            day4::part2_reader(reader, day4::PastEnd::Error).map(|v| v.to_string())
+        }
+        ("day7", "part1") => day7::part1_reader(reader).map(|v| v.to_string()),
+        ("day7", "part2") => day7::part2_reader(reader).map(|v| v.to_string()),
+        _ => Err(format!("{} {} has no streaming mode", day, part)),
+    }
+}
+
+/// Like `solve`, but splitting the work across `threads` threads for the days
+/// that can.
+fn solve_parallel(day: &str, part: &str, path: &str, threads: usize) -> Result<String, String> {
+    let input = std::fs::read(path).map_err(|e| format!("File read error: {}", e))?;
+    let text = || std::str::from_utf8(&input).map_err(|e| format!("File read error: {}", e));
+    let bag = day2::Bag::default();
+    match (day, part) {
+        ("day1", "part1") => day1::part1_parallel(&input, threads).map(|v| v.to_string()),
+        ("day1", "part2") => day1::part2_parallel(&input, threads).map(|v| v.to_string()),
+        ("day2", "part1") => day2::part1_parallel(text()?, &bag, threads).map(|v| v.to_string()),
+        ("day2", "part2") => day2::part2_parallel(text()?, threads).map(|v| v.to_string()),
+        ("day5", "part2") => day5::part2_parallel(text()?, threads).map(|v| v.to_string()),
+        ("day8", "part2") => day8::part2_parallel(text()?, threads).map(|v| v.to_string()),
+        _ => Err(format!("{} {} has no parallel mode", day, part)),
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a random input for a day
+    Generate {
+        day: String,
+
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Lines, games, cards, ... depending on the day
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+
+        /// Day specific shape setting as key=value, may be repeated
+        #[arg(long = "knob")]
+        knobs: Vec<String>,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Check a day's sample and real answers whenever its files change
+    Watch {
+        day: String,
+
+        /// Milliseconds between checks for changes
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+
+        /// Rebuild and restart when a source file changes
+        #[arg(long)]
+        rebuild: bool,
+    },
+    /// Download a day's input into the per-user cache, which the runner then
+    /// uses instead of src/<day>/input.txt
+    Fetch {
+        day: String,
+
+        #[arg(long, env = "AOC_BASE_URL", default_value = fetch::DEFAULT_BASE_URL)]
+        base_url: String,
+
+        /// Value of the site's session cookie
+        #[arg(long, env = "AOC_SESSION", hide_env_values = true)]
+        session: String,
+
+        /// Download again even if the input is already cached
+        #[arg(long)]
+        force: bool,
+    },
+    /// Solve a day's part on its real input and send the answer, unless an
+    /// earlier submission rules it out or asked to wait
+    Submit {
+        day: String,
+        part: String,
+
+        #[arg(long, env = "AOC_BASE_URL", default_value = fetch::DEFAULT_BASE_URL)]
+        base_url: String,
+
+        /// Value of the site's session cookie
+        #[arg(long, env = "AOC_SESSION", hide_env_values = true)]
+        session: String,
+    },
+    /// Parse a day's input once and answer commands about it (day3, day5,
+    /// day7 and day8)
+    Repl {
+        day: String,
+
+        #[arg(short = 's', long)]
+        use_sample_input: bool,
+    },
 }
 
 fn main() {
     let args = Args::parse();
-    let day = args.day.as_str();
-    let part = args.part.as_str();
-
-    let path = format!(
-        "src/{}/{}input.txt",
-        day,
-        if args.use_sample_input {
-            args.part.clone() + "_sample_"
-        } else {
-            "".to_string()
+    if let Some(command) = &args.command {
+        match command {
+            Command::Generate {
+                day,
+                seed,
+                size,
+                knobs,
+                output,
+            } => {
+                let knobs = generate::Knobs::parse(knobs).unwrap();
+                let input = generate::generate(day, *seed, *size, &knobs).unwrap();
+                match output {
+                    Some(path) => std::fs::write(path, input).unwrap(),
+                    None => println!("{}", input),
+                }
+            }
+            Command::Watch {
+                day,
+                interval,
+                rebuild,
+            } => watch::watch(day, Duration::from_millis(*interval), *rebuild).unwrap(),
+            Command::Fetch {
+                day,
+                base_url,
+                session,
+                force,
+            } => {
+                let client = fetch::Client::new(base_url, session);
+                match fetch::cache_dir()
+                    .and_then(|cache| fetch::fetch(&client, &cache, day, *force))
+                {
+                    Ok(path) => println!("{}", path.display()),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Command::Submit {
+                day,
+                part,
+                base_url,
+                session,
+            } => {
+                let submitted = solve(day, part, &fetch::input_path(day)).and_then(|answer| {
+                    println!("{} {}: {}", day, part, answer);
+                    let client = fetch::Client::new(base_url, session);
+                    let mut history = submit::History::load(&fetch::cache_dir()?)?;
+                    let now = SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs();
+                    submit::submit(&client, &mut history, day, part, &answer, now)
+                });
+                match submitted {
+                    Ok(outcome) => println!("{}", outcome),
+                    Err(e) => {
+                        eprintln!("Submit failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Command::Repl {
+                day,
+                use_sample_input,
+            } => {
+                let path = if *use_sample_input {
+                    format!("src/{}/part1_sample_input.txt", day)
+                } else {
+                    fetch::input_path(day)
+                };
+                let input = std::fs::read_to_string(path).unwrap();
+                let stdin = std::io::stdin();
+                let prompt = format!("{}> ", day);
+                let prompt = stdin.is_terminal().then_some(prompt.as_str());
+                adventofcode2023::repl::repl(day, &input, stdin.lock(), std::io::stdout(), prompt)
+                    .unwrap();
+            }
         }
-    );
+        return;
+    }
+    // clap requires both when there is no subcommand
+    let day = args.day.as_deref().unwrap();
+    let part = args.part.as_deref().unwrap();
+
+    let path = if args.use_sample_input {
+        sample_path(day, part)
+    } else {
+        fetch::input_path(day)
+    };
 
     // check path exists
     if !std::path::Path::new(&path).exists() {
@@ -41,23 +359,405 @@ fn main() {
         return;
     }
 
+    if let Err(e) = run(&args, day, part, &path) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Runs `part` of `day` on the input at `path`, printing the answer.
+fn run(args: &Args, day: &str, part: &str, path: &str) -> Result<(), String> {
     match (day, part) {
-        ("day1", "part1") => println!("{}", day1::part1(&path)),
-        ("day1", "part2") => println!("{}", day1::part2(&path)),
-        ("day2", "part1") => println!("{}", day2::part1(&path)),
-        ("day2", "part2") => println!("{}", day2::part2(&path)),
-        ("day3", "part1") => println!("{}", day3::part1(&path)),
-        ("day3", "part2") => println!("{}", day3::part2(&path)),
-        ("day4", "part1") => println!("{}", day4::part1(&path)),
-        ("day4", "part2") => println!("{}", day4::part2(&path)),
-        ("day5", "part1") => println!("{}", day5::part1(&path).unwrap()),
-        ("day5", "part2") => println!("{}", day5::part2(&path).unwrap()),
-        ("day6", "part1") => println!("{}", day6::part1(&path).unwrap()),
-        ("day6", "part2") => println!("{}", day6::part2(&path).unwrap()),
-        ("day7", "part1") => println!("{}", day7::part1(&path).unwrap()),
-        ("day7", "part2") => println!("{}", day7::part2(&path).unwrap()),
-        ("day8", "part1") => println!("{}", day8::part1(&path).unwrap()),
-        ("day8", "part2") => println!("{}", day8::part2(&path).unwrap()),
-        _ => panic!("Bad day/part number. Args should be: day1 part1"),
+        ("day1", "part1" | "part2" | "calibrate") if args.explain => {
+            let vocabulary = match part {
+                "part1" => day1::Vocabulary::digits(),
+                "part2" => day1::Vocabulary::english(),
+                _ => day1::Vocabulary::load(&args.vocabulary)?,
+            };
+            let combine = match part {
+                "calibrate" => args.combine.parse()?,
+                _ => day1::Combine::Concat,
+            };
+            let lines = day1::explain(path, &vocabulary, combine)?;
+            for line in lines.iter() {
+                println!("{}", line);
+            }
+            let no_digits = lines.iter().filter(|l| l.tokens.is_none()).count();
+            println!("{} lines, {} without digits", lines.len(), no_digits);
+            println!("{}", lines.iter().map(|l| l.value).sum::<u64>());
+        }
+        ("day1", "calibrate") => {
+            let vocabulary = day1::Vocabulary::load(&args.vocabulary)?;
+            let combine = args.combine.parse()?;
+            let total = if args.stream {
+                day1::calibrate_reader(open(path)?, &vocabulary, combine)
+            } else if args.threads != 1 {
+                let input = std::fs::read(path).map_err(|e| format!("File read error: {}", e))?;
+                day1::calibrate_parallel(&input, &vocabulary, combine, args.threads)
+            } else {
+                day1::calibrate(path, &vocabulary, combine)
+            };
+            println!("{}", total?)
+        }
+        ("day2", "part1" | "part2" | "report") => {
+            let bag = match &args.bag {
+                Some(spec) => day2::Bag::load(spec)?,
+                None => day2::Bag::default(),
+            };
+            match part {
+                "part1" if args.stream => {
+                    println!("{}", day2::part1_reader(open(path)?, &bag)?)
+                }
+                "part2" if args.stream => {
+                    println!("{}", day2::part2_reader(open(path)?)?)
+                }
+                "part1" if args.threads != 1 => {
+                    let input = std::fs::read_to_string(path)
+                        .map_err(|e| format!("File read error: {}", e))?;
+                    let total = day2::part1_parallel(&input, &bag, args.threads);
+                    println!("{}", total?)
+                }
+                "part2" if args.threads != 1 => {
+                    let input = std::fs::read_to_string(path)
+                        .map_err(|e| format!("File read error: {}", e))?;
+                    let total = day2::part2_parallel(&input, args.threads);
+                    println!("{}", total?)
+                }
+                "part1" => println!("{}", day2::part1(path, &bag)?),
+                "part2" => println!("{}", day2::part2(path)?),
+                _ => {
+                    let reports = day2::report(path, &bag)?;
+                    for report in reports.iter() {
+                        println!("{}", report);
+                    }
+                    for (color, count) in day2::ruled_out_by_color(&reports, &bag) {
+                        println!("{} rules out {} games", color, count);
+                    }
+                    let possible = reports.iter().filter(|r| r.possible());
+                    println!("possible id sum {}", possible.map(|r| r.id).sum::<u32>());
+                    println!(
+                        "power sum {}",
+                        reports.iter().map(|r| r.power()).sum::<u64>()
+                    );
+                }
+            }
+        }
+        ("day3", "rules") => {
+            let rules = args.rules.parse()?;
+            println!("{}", day3::score(path, &rules)?)
+        }
+        ("day3", "render") => {
+            let color = !args.plain
+                && std::io::stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none();
+            print!("{}", day3::render(path, color)?)
+        }
+        ("day4", "part2" | "trace") => {
+            let past_end = if args.clamp {
+                day4::PastEnd::Clamp
+            } else {
+                day4::PastEnd::Error
+            };
+            if part == "part2" && args.stream {
+                let reader = open(path)?;
+                println!("{}", day4::part2_reader(reader, past_end)?);
+            } else if part == "part2" {
+                println!("{}", day4::part2(path, past_end)?);
+            } else {
+                let (steps, total) = day4::trace(path, past_end)?;
+                for step in steps.iter() {
+                    println!("{}", step);
+                }
+                println!("{}", total);
+            }
+        }
+        ("day5", "brute") => println!("{}", day5::part2_brute_force(path, args.threads)?),
+        ("day8", "dot") => print!(
+            "{}",
+            day8::dot(
+                path,
+                &args.start,
+                &args.goal,
+                args.highlight,
+                args.instructions.as_deref(),
+            )?
+        ),
+        ("day8", "trace") => {
+            let mut trace = day8::PathTrace::new(
+                args.trace_limit,
+                args.trace_show,
+                args.trace_file.as_deref(),
+            )?;
+            let steps = day8::trace(
+                path,
+                &args.start,
+                &args.goal,
+                args.instructions.as_deref(),
+                &mut trace,
+            );
+            print!("{}", trace.summary());
+            println!("{}", steps?)
+        }
+        _ if args.stream => println!("{}", solve_stream(day, part, path)?),
+        _ if args.threads != 1 => println!("{}", solve_parallel(day, part, path, args.threads)?),
+        _ => println!("{}", solve(day, part, path)?),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use answers::Answers;
+
+    /// Runs every `src/dayN/partM_sample_input.txt` through partM and checks
+    /// it against that day's answers.txt. Every day needs both samples and
+    /// their answers.
+    #[test]
+    fn sample_inputs_match_answers() {
+        let mut days: Vec<String> = std::fs::read_dir("src")
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("day"))
+            .collect();
+        days.sort();
+        assert!(!days.is_empty(), "no day directories found under src/");
+
+        let mut failures = Vec::new();
+        for day in days.iter() {
+            let answers = match Answers::load(day) {
+                Ok(answers) => answers,
+                Err(e) => {
+                    failures.push(format!("{}: {}", day, e));
+                    continue;
+                }
+            };
+            for part in ["part1", "part2"] {
+                let file = format!("{}_sample_input.txt", part);
+                let path = format!("src/{}/{}", day, file);
+                if !std::path::Path::new(&path).exists() {
+                    failures.push(format!("{} {}: no {}", day, part, path));
+                    continue;
+                }
+                let Some(expected) = answers.expected(part, &file) else {
+                    failures.push(format!("{} {}: no answer for {}", day, part, file));
+                    continue;
+                };
+                match solve(day, part, &path) {
+                    Ok(actual) if actual == expected => {}
+                    Ok(actual) => failures.push(format!(
+                        "{} {}: expected {}, got {}",
+                        day, part, expected, actual
+                    )),
+                    Err(e) => failures.push(format!("{} {}: {}", day, part, e)),
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    /// Inputs fetched from the site end with a newline, where some of the
+    /// checked-in ones do not, so every day has to accept one.
+    #[test]
+    fn inputs_with_a_trailing_newline_match_answers() {
+        let mut failures = Vec::new();
+        for day in (1..=8).map(|day| format!("day{}", day)) {
+            let answers = Answers::load(&day).unwrap();
+            let input = std::fs::read_to_string(format!("src/{}/input.txt", day)).unwrap();
+            let path = std::env::temp_dir().join(format!(
+                "trailing_newline_{}_{}.txt",
+                day,
+                std::process::id()
+            ));
+            std::fs::write(&path, input + "\n").unwrap();
+            for part in ["part1", "part2"] {
+                let expected = answers.expected(part, "input.txt");
+                let actual = solve(&day, part, &path.to_string_lossy());
+                if actual.as_deref().ok() != expected {
+                    failures.push(format!(
+                        "{} {}: expected {:?}, got {:?}",
+                        day, part, expected, actual
+                    ));
+                }
+            }
+            std::fs::remove_file(path).unwrap();
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    /// Both ways of running each streaming day on `input`. The reader's
+    /// small buffer makes lines span reads.
+    fn in_memory_and_streamed(
+        day: &str,
+        part: &str,
+        input: &str,
+    ) -> (Result<String, String>, Result<String, String>) {
+        let reader = BufReader::with_capacity(7, input.as_bytes());
+        let bag = day2::Bag::default();
+        let past_end = day4::PastEnd::Error;
+        let (in_memory, streamed) = match (day, part) {
+            ("day1", "part1") => (
+                day1::part1_bytes(input.as_bytes()),
+                day1::part1_reader(reader),
+            ),
+            ("day1", "part2") => (
+                day1::part2_bytes(input.as_bytes()),
+                day1::part2_reader(reader),
+            ),
+            ("day2", "part1") => (
+                day2::part1_str(input, &bag).map(u64::from),
+                day2::part1_reader(reader, &bag).map(u64::from),
+            ),
+            ("day2", "part2") => (day2::part2_str(input), day2::part2_reader(reader)),
+            ("day4", "part1") => {
+                let to_string = |v: u128| v.to_string();
+                return (
+                    day4::part1_str(input).map(to_string),
+                    day4::part1_reader(reader).map(to_string),
+                );
+            }
+            ("day4", "part2") => {
+                let to_string = |v: u128| v.to_string();
+                return (
+                    day4::part2_str(input, past_end).map(to_string),
+                    day4::part2_reader(reader, past_end).map(to_string),
+                );
+            }
+            ("day7", "part1") => (day7::part1_str(input), day7::part1_reader(reader)),
+            ("day7", "part2") => (day7::part2_str(input), day7::part2_reader(reader)),
+            _ => panic!("{} {} has no streaming mode", day, part),
+        };
+        (
+            in_memory.map(|v| v.to_string()),
+            streamed.map(|v| v.to_string()),
+        )
+    }
+
+    #[test]
+    fn streaming_matches_in_memory() {
+        let mut failures = Vec::new();
+        for day in ["day1", "day2", "day4", "day7"] {
+            let mut inputs: Vec<(String, String)> = std::fs::read_dir(format!("src/{}", day))
+                .unwrap()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.to_string_lossy().ends_with("input.txt"))
+                .map(|path| {
+                    let input = std::fs::read_to_string(&path).unwrap();
+                    (path.to_string_lossy().to_string(), input)
+                })
+                .collect();
+            for seed in 0..8 {
+                let knobs = generate::Knobs::parse(&[]).unwrap();
+                let input = generate::generate(day, seed, 150, &knobs).unwrap();
+                inputs.push((format!("generated seed {}", seed), input));
+                // Trailing newlines and blank lines at the end, then one
+                // between the first two lines
+                let input = inputs.last().unwrap().1.clone() + "\n\n";
+                inputs.push((format!("generated seed {} with blank lines", seed), input));
+                let input = inputs.last().unwrap().1.replacen('\n', "\n\n", 1);
+                inputs.push((format!("generated seed {} with a blank line", seed), input));
+            }
+
+            for part in ["part1", "part2"] {
+                for (name, input) in inputs.iter() {
+                    let (in_memory, streamed) = in_memory_and_streamed(day, part, input);
+                    if in_memory != streamed {
+                        failures.push(format!(
+                            "{} {} on {}: {:?} in memory, {:?} streamed",
+                            day, part, name, in_memory, streamed
+                        ));
+                    }
+                }
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut failures = Vec::new();
+        for (day, parts) in [
+            ("day1", &["part1", "part2"][..]),
+            ("day2", &["part1", "part2"]),
+            ("day5", &["part2"]),
+            ("day8", &["part2"]),
+        ] {
+            let dir = format!("src/{}", day);
+            let mut paths: Vec<String> = std::fs::read_dir(&dir)
+                .unwrap()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().to_string_lossy().to_string())
+                .filter(|path| path.ends_with("input.txt"))
+                .collect();
+            let knobs = generate::Knobs::parse(&[]).unwrap();
+            let mut generated = Vec::new();
+            for seed in 0..4 {
+                let path = std::env::temp_dir().join(format!(
+                    "parallel_{}_{}_{}.txt",
+                    day,
+                    seed,
+                    std::process::id()
+                ));
+                std::fs::write(&path, generate::generate(day, seed, 40, &knobs).unwrap()).unwrap();
+                paths.push(path.to_string_lossy().to_string());
+                generated.push(path);
+            }
+
+            for part in parts {
+                for path in paths.iter() {
+                    let sequential = solve(day, part, path).ok();
+                    for threads in [1, 2, 3, 7] {
+                        let parallel = solve_parallel(day, part, path, threads).ok();
+                        if parallel != sequential {
+                            failures.push(format!(
+                                "{} {} on {} with {} threads: {:?}, sequential {:?}",
+                                day, part, path, threads, parallel, sequential
+                            ));
+                        }
+                    }
+                }
+            }
+            for path in generated {
+                std::fs::remove_file(path).unwrap();
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn modes_use_the_sample_of_the_part_they_extend() {
+        assert_eq!(
+            sample_path("day1", "part1"),
+            "src/day1/part1_sample_input.txt"
+        );
+        assert_eq!(
+            sample_path("day1", "part2"),
+            "src/day1/part2_sample_input.txt"
+        );
+        assert_eq!(
+            sample_path("day5", "brute"),
+            "src/day5/part2_sample_input.txt"
+        );
+        assert_eq!(
+            sample_path("day8", "dot"),
+            "src/day8/part1_sample_input.txt"
+        );
+        for (day, mode, _) in MODES {
+            assert!(std::path::Path::new(&sample_path(day, mode)).exists());
+        }
+    }
+
+    #[test]
+    fn day5_brute_force_matches_range_walk() {
+        let knobs = generate::Knobs::parse(&["seed_len=200".to_string()]).unwrap();
+        for seed in 0..4 {
+            let input = generate::generate("day5", seed, 10, &knobs).unwrap();
+            let expected = day5::part2_str(&input);
+            for threads in [1, 2, 5] {
+                assert_eq!(day5::part2_brute_force_str(&input, threads), expected);
+            }
+        }
     }
 }
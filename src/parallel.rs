@@ -0,0 +1,101 @@
+//! Splitting independent work across threads. Results always come back in
+//! input order, so combining them gives the same answer for any thread count.
+
+use std::{ops::Range, thread};
+
+/// `threads` as given, or one per core for 0.
+pub fn thread_count(threads: usize) -> usize {
+    match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        _ => threads,
+    }
+}
+
+/// Runs `f` on up to `threads` contiguous chunks of `items` at once, giving
+/// back each chunk's result in order. With one thread, `f` runs on the
+/// calling thread.
+pub fn map_chunks<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&[T]) -> R + Sync,
+) -> Vec<R> {
+    let threads = thread_count(threads);
+    if threads == 1 || items.len() <= 1 {
+        return vec![f(items)];
+    }
+    let chunk_len = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || f(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+/// Splits `input` into up to `pieces` byte ranges of about equal size, each
+/// ending just after a newline (or at the end), with the 0-based index of
+/// its first line.
+pub fn line_ranges(input: &[u8], pieces: usize) -> Vec<(usize, Range<usize>)> {
+    let pieces = thread_count(pieces);
+    let mut ranges = Vec::with_capacity(pieces);
+    let mut start = 0;
+    let mut first_line = 0;
+    for piece in 1..=pieces {
+        let from = start.max(input.len() * piece / pieces);
+        let end = match input[from..].iter().position(|c| *c == b'\n') {
+            Some(offset) if piece < pieces => from + offset + 1,
+            _ => input.len(),
+        };
+        ranges.push((first_line, start..end));
+        first_line += input[start..end].iter().filter(|c| **c == b'\n').count();
+        start = end;
+        if start == input.len() {
+            break;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_ranges_cover_input_on_line_boundaries() {
+        let input = b"one\ntwo\n\nthree\nfour\nfive";
+        for pieces in 1..12 {
+            let ranges = line_ranges(input, pieces);
+            assert!(ranges.len() <= pieces);
+            let mut next = 0;
+            for (first_line, range) in ranges.iter() {
+                assert_eq!(range.start, next);
+                assert_eq!(
+                    *first_line,
+                    input[..range.start].iter().filter(|c| **c == b'\n').count()
+                );
+                assert!(range.end == input.len() || input[range.end - 1] == b'\n');
+                next = range.end;
+            }
+            assert_eq!(next, input.len());
+        }
+        assert_eq!(line_ranges(b"", 4), vec![(0, 0..0)]);
+    }
+
+    #[test]
+    fn map_chunks_keeps_order() {
+        let items: Vec<u32> = (0..100).collect();
+        for threads in 1..9 {
+            let sums = map_chunks(&items, threads, |chunk| chunk.to_vec());
+            assert_eq!(sums.concat(), items);
+        }
+    }
+}
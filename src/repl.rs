@@ -0,0 +1,139 @@
+//! Querying a parsed puzzle input interactively. Each day that supports it
+//! has a `Session` holding its parsed input, which answers one command line
+//! at a time.
+
+use std::io::{BufRead, Write};
+
+use crate::{day3, day5, day7, day8};
+
+pub trait Commands {
+    /// One line per command: its arguments and what it prints.
+    fn help(&self) -> &'static str;
+
+    /// Runs one command, split on whitespace and never empty.
+    fn run(&mut self, words: &[&str]) -> Result<String, String>;
+}
+
+/// Reads commands from `commands` until it ends or `quit`, writing each
+/// answer, or the error for a bad command, to `out`. Prints `prompt` before
+/// each command when given.
+pub fn run(
+    session: &mut dyn Commands,
+    commands: impl BufRead,
+    mut out: impl Write,
+    prompt: Option<&str>,
+) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Write error: {}", e);
+    let help = format!("{}help\nquit\n", session.help());
+    let show_prompt = |out: &mut dyn Write| match prompt {
+        Some(prompt) => write!(out, "{}", prompt)
+            .and_then(|_| out.flush())
+            .map_err(write_error),
+        None => Ok(()),
+    };
+
+    show_prompt(&mut out)?;
+    for line in commands.lines() {
+        let line = line.map_err(|e| format!("Read error: {}", e))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let reply = match words.first() {
+            None => None,
+            Some(&"quit" | &"exit") => break,
+            Some(&"help") => Some(help.clone()),
+            Some(_) => Some(match session.run(&words) {
+                Ok(answer) => format!("{}\n", answer),
+                Err(e) => format!("error: {}\n", e),
+            }),
+        };
+        if let Some(reply) = reply {
+            write!(out, "{}", reply).map_err(write_error)?;
+        }
+        show_prompt(&mut out)?;
+    }
+    Ok(())
+}
+
+/// Parses `input` as `day`'s puzzle input and runs a session on it.
+pub fn repl(
+    day: &str,
+    input: &str,
+    commands: impl BufRead,
+    out: impl Write,
+    prompt: Option<&str>,
+) -> Result<(), String> {
+    match day {
+        "day3" => run(
+            &mut day3::Session::new(input.as_bytes())?,
+            commands,
+            out,
+            prompt,
+        ),
+        "day5" => run(&mut day5::Session::new(input)?, commands, out, prompt),
+        "day7" => run(&mut day7::Session::new(input)?, commands, out, prompt),
+        "day8" => run(&mut day8::Session::new(input)?, commands, out, prompt),
+        _ => Err(format!("{} has no repl, try day3, day5, day7 or day8", day)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(day: &str, file: &str, commands: &str) -> String {
+        let input = std::fs::read_to_string(format!("src/{}/{}", day, file)).unwrap();
+        let mut out = Vec::new();
+        repl(day, &input, commands.as_bytes(), &mut out, None).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn sessions_answer_queries_on_samples() {
+        assert_eq!(
+            session(
+                "day3",
+                "part1_sample_input.txt",
+                "at 1,0\nat 3,1\nat 7,5\nat 9,9\n"
+            ),
+            "(1,0) '6': 467, a part number, touching '*' at (3,1)\n\
+             (3,1) '*': touching 467, 35, a gear with ratio 16345\n\
+             (7,5) '5': 58, not a part number\n\
+             (9,9) '.'\n"
+        );
+        assert_eq!(
+            session(
+                "day5",
+                "part1_sample_input.txt",
+                "map seed 79\nmap light 77\n"
+            ),
+            "seed 79 -> soil 81 -> fertilizer 81 -> water 81 -> light 74 -> \
+             temperature 78 -> humidity 78 -> location 82\n\
+             light 77 -> temperature 45 -> humidity 46 -> location 46\n"
+        );
+        assert_eq!(
+            session("day7", "part1_sample_input.txt", "rank KK677\nrank AAAAA\n"),
+            "part1: TwoPair, rank 3 of 5, bid 28\n\
+             part2: TwoPair, rank 2 of 5, bid 28\n\
+             part1: FiveOfAKind, would rank 6 of 6\n\
+             part2: FiveOfAKind, would rank 6 of 6\n"
+        );
+        assert_eq!(
+            session("day8", "part1_sample_input.txt", "walk AAA LRL\nnode AAA\n"),
+            "AAA -L-> BBB -R-> EEE -L-> EEE\nAAA = (BBB, CCC)\n"
+        );
+    }
+
+    #[test]
+    fn bad_commands_report_errors_and_continue() {
+        let out = session(
+            "day8",
+            "part1_sample_input.txt",
+            "\nfly AAA\nwalk QQQ L\nwalk AAA X\nhelp\nsteps AAA\nquit\nnode AAA\n",
+        );
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "error: Unknown command fly, try help");
+        assert_eq!(lines[1], "error: Node QQQ not found");
+        assert_eq!(lines[2], "error: Invalid instruction X");
+        assert!(lines[3].starts_with("node <name>"));
+        assert_eq!(lines.last(), Some(&"2"));
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    fmt, fs,
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::fetch::{day_number, Client};
+
+/// What the site said about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong with no hint
+    Wrong,
+    /// Not checked, submitting again is allowed after the wait
+    TooSoon,
+    /// Not checked, the part is already solved or not unlocked yet
+    WrongLevel,
+}
+
+impl Outcome {
+    fn is_wrong(&self) -> bool {
+        matches!(self, Outcome::TooHigh | Outcome::TooLow | Outcome::Wrong)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Correct => "correct",
+            Outcome::TooHigh => "too_high",
+            Outcome::TooLow => "too_low",
+            Outcome::Wrong => "wrong",
+            Outcome::TooSoon => "too_soon",
+            Outcome::WrongLevel => "wrong_level",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "correct" => Ok(Outcome::Correct),
+            "too_high" => Ok(Outcome::TooHigh),
+            "too_low" => Ok(Outcome::TooLow),
+            "wrong" => Ok(Outcome::Wrong),
+            "too_soon" => Ok(Outcome::TooSoon),
+            "wrong_level" => Ok(Outcome::WrongLevel),
+            _ => Err(format!("Unknown outcome {}", s)),
+        }
+    }
+}
+
+/// Seconds in a wait like "1m 5s" or "30s".
+fn parse_wait(text: &str) -> Option<u64> {
+    let mut total = 0;
+    for word in text.split_whitespace() {
+        let (value, unit) = word.split_at(word.find(|c: char| !c.is_ascii_digit())?);
+        let seconds = match unit {
+            "h" => 3600,
+            "m" => 60,
+            "s" => 1,
+            _ => return None,
+        };
+        total += value.parse::<u64>().ok()? * seconds;
+    }
+    Some(total)
+}
+
+/// Minutes in "wait one minute before trying again", or "wait 5 minutes"
+/// after several wrong answers.
+fn parse_cooldown(text: &str) -> Option<u64> {
+    text.split("wait ").skip(1).find_map(|rest| {
+        let mut words = rest.split_whitespace();
+        let count = match words.next()? {
+            "one" => 1,
+            count => count.parse().ok()?,
+        };
+        matches!(words.next()?, "minute" | "minutes").then_some(count)
+    })
+}
+
+/// The page's `<article>`, which holds the verdict. The rest of the page is
+/// navigation and sponsors, and may say anything.
+fn article(page: &str) -> Option<&str> {
+    let start = page.find("<article")?;
+    let end = page[start..].find("</article>")?;
+    Some(&page[start..start + end])
+}
+
+/// Seconds to wait after a wrong answer whose cooldown could not be read,
+/// erring long as the site asks for more after repeated guesses.
+const UNREAD_COOLDOWN: u64 = 15 * 60;
+
+/// The outcome in the response page, and how many seconds to wait before
+/// submitting again. The wait is None when a wrong answer's cooldown cannot
+/// be read, as the answer still has to be recorded.
+fn parse_response(page: &str) -> Result<(Outcome, Option<u64>), String> {
+    let text = article(page).ok_or("No <article> in the response to the answer".to_string())?;
+    if text.contains("That's the right answer") {
+        return Ok((Outcome::Correct, Some(0)));
+    }
+    if text.contains("You don't seem to be solving the right level") {
+        return Ok((Outcome::WrongLevel, Some(0)));
+    }
+    if text.contains("You gave an answer too recently") {
+        // "...You have 1m 5s left to wait."
+        let wait = text
+            .split_once(" left to wait")
+            .and_then(|(before, _)| before.rsplit_once("have "))
+            .and_then(|(_, wait)| parse_wait(wait))
+            .ok_or("Failed to read the wait from a too soon response".to_string())?;
+        return Ok((Outcome::TooSoon, Some(wait)));
+    }
+    if text.contains("That's not the right answer") {
+        let outcome = if text.contains("your answer is too high") {
+            Outcome::TooHigh
+        } else if text.contains("your answer is too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Wrong
+        };
+        return Ok((outcome, parse_cooldown(text).map(|minutes| minutes * 60)));
+    }
+    Err("Unrecognised response to the answer".to_string())
+}
+
+/// One submission, as a line of the history file:
+/// `<sent at> <day> <part> <answer> <outcome> <wait>`, times in Unix seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    sent_at: u64,
+    day: String,
+    part: String,
+    answer: String,
+    outcome: Outcome,
+    wait: u64,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            self.sent_at, self.day, self.part, self.answer, self.outcome, self.wait
+        )
+    }
+}
+
+impl FromStr for Entry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            [sent_at, day, part, answer, outcome, wait] => Ok(Entry {
+                sent_at: sent_at
+                    .parse()
+                    .map_err(|e| format!("Bad time {}: {}", sent_at, e))?,
+                day: day.to_string(),
+                part: part.to_string(),
+                answer: answer.to_string(),
+                outcome: outcome.parse()?,
+                wait: wait
+                    .parse()
+                    .map_err(|e| format!("Bad wait {}: {}", wait, e))?,
+            }),
+            _ => Err("expected <sent at> <day> <part> <answer> <outcome> <wait>".to_string()),
+        }
+    }
+}
+
+/// Every answer submitted from this user's cache, oldest first.
+pub struct History {
+    path: PathBuf,
+    entries: Vec<Entry>,
+}
+
+impl History {
+    pub fn load(cache: &Path) -> Result<History, String> {
+        let path = cache.join("submissions.txt");
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        let mut entries = Vec::new();
+        for (line_idx, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = line
+                .parse()
+                .map_err(|e| format!("{} line {}: {}", path.display(), line_idx + 1, e))?;
+            entries.push(entry);
+        }
+        Ok(History { path, entries })
+    }
+
+    fn record(&mut self, entry: Entry) -> Result<(), String> {
+        let dir = self.path.parent().unwrap();
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", entry))
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// When the site will take another answer. The wait applies to every
+    /// day and part.
+    fn ready_at(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|entry| entry.sent_at + entry.wait)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Why `answer` should not be sent for `day` `part`: the part is solved,
+    /// the same answer was wrong before, or an earlier too high or too low
+    /// answer already rules it out.
+    fn ruled_out(&self, day: &str, part: &str, answer: &str) -> Option<String> {
+        let number = answer.parse::<i128>().ok();
+        for entry in self
+            .entries
+            .iter()
+            .filter(|entry| entry.day == day && entry.part == part)
+        {
+            let bound = entry.answer.parse::<i128>().ok();
+            let ruled_out = match (entry.outcome, number, bound) {
+                (Outcome::Correct, _, _) => {
+                    return Some(format!(
+                        "{} {} is already solved with {}",
+                        day, part, entry.answer
+                    ))
+                }
+                (outcome, _, _) if outcome.is_wrong() && entry.answer == answer => true,
+                (Outcome::TooHigh, Some(number), Some(bound)) => number >= bound,
+                (Outcome::TooLow, Some(number), Some(bound)) => number <= bound,
+                _ => false,
+            };
+            if ruled_out {
+                return Some(format!(
+                    "{} for {} {} is ruled out by {} being {}",
+                    answer, day, part, entry.answer, entry.outcome
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Sends `answer` for `day` `part` unless the history rules it out or the
+/// last response asked to wait past `now`, recording what comes back.
+pub fn submit(
+    client: &Client,
+    history: &mut History,
+    day: &str,
+    part: &str,
+    answer: &str,
+    now: u64,
+) -> Result<Outcome, String> {
+    let level = match part {
+        "part1" => "1",
+        "part2" => "2",
+        _ => return Err(format!("Bad part {}, expected part1 or part2", part)),
+    };
+    if answer.is_empty() || answer.contains(char::is_whitespace) {
+        return Err(format!("Bad answer {:?}", answer));
+    }
+    if let Some(reason) = history.ruled_out(day, part, answer) {
+        return Err(reason);
+    }
+    let ready_at = history.ready_at();
+    if now < ready_at {
+        return Err(format!(
+            "Submitting too soon, wait {}s more",
+            ready_at - now
+        ));
+    }
+
+    let page = client.post_form(
+        &format!("/day/{}/answer", day_number(day)?),
+        &[("level", level), ("answer", answer)],
+    )?;
+    let (outcome, wait) = parse_response(&page)?;
+    history.record(Entry {
+        sent_at: now,
+        day: day.to_string(),
+        part: part.to_string(),
+        answer: answer.to_string(),
+        outcome,
+        wait: wait.unwrap_or(UNREAD_COOLDOWN),
+    })?;
+    match wait {
+        Some(_) => Ok(outcome),
+        None => Err(format!(
+            "{} was {}, but the wait could not be read from the response, so {}s is assumed",
+            answer, outcome, UNREAD_COOLDOWN
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stub_server::StubServer;
+
+    const RIGHT: &str =
+        "<article><p>That's the right answer! You are one gold star closer.</p></article>";
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high. \
+        Please wait one minute before trying again.</p></article>";
+    const TOO_LOW_SLOWER: &str =
+        "<article><p>That's not the right answer; your answer is too low. \
+        Please wait 5 minutes before trying again.</p></article>";
+    const TOO_SOON: &str = "<article><p>You gave an answer too recently; you have to wait after \
+        submitting an answer before trying again. You have 1m 5s left to wait.</p></article>";
+    const WRONG_LEVEL: &str = "<article><p>You don't seem to be solving the right level. \
+        Did you already complete it?</p></article>";
+    const UNREADABLE: &str = "<article><p>That's not the right answer. \
+        Please wait a while before trying again.</p></article>";
+    /// A whole response page, with waits mentioned outside the article.
+    const FULL_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en-us">
+<head>
+<meta charset="utf-8"/>
+<title>Day 1 - Advent of Code 2023</title>
+<script>window.addEventListener('click', function() { /* wait 3 ticks */ });</script>
+</head><!--
+Please don't wait 2 minutes to read this comment.
+-->
+<body>
+<header><div><h1 class="title-global"><a href="/">Advent of Code</a></h1><nav><ul><li><a href="/2023/about">[About]</a></li><li><a href="/2023/events">[Events]</a></li><li><a href="/2023/settings">[Settings]</a></li><li><a href="/2023/auth/logout">[Log Out]</a></li></ul></nav><div class="user">someone <span class="star-count">12*</span></div></div></header>
+<div id="sidebar">
+<div id="sponsor"><div class="quiet">Our <a href="/2023/sponsors">sponsors</a> help make Advent of Code possible:</div><div class="sponsor">Why wait 9 minutes for a build? Try our CI.</div></div>
+</div><!--/sidebar-->
+<main>
+<article><p>That's not the right answer; your answer is too low.  If you're stuck, make sure you're using the full input data; there are also some general tips on the <a href="/2023/about">about page</a>, or you can ask for hints on the <a href="https://www.reddit.com/r/adventofcode/" target="_blank">subreddit</a>.  Because you have guessed incorrectly 6 times on this puzzle, please wait 10 minutes before trying again. <a href="/2023/day/1">[Return to Day 1]</a></p></article>
+</main>
+</body>
+</html>"#;
+
+    fn temp_history(name: &str) -> History {
+        let dir = std::env::temp_dir().join(format!("submit_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        History::load(&dir).unwrap()
+    }
+
+    #[test]
+    fn responses_parse() {
+        assert_eq!(parse_response(RIGHT), Ok((Outcome::Correct, Some(0))));
+        assert_eq!(parse_response(TOO_HIGH), Ok((Outcome::TooHigh, Some(60))));
+        assert_eq!(
+            parse_response(TOO_LOW_SLOWER),
+            Ok((Outcome::TooLow, Some(300)))
+        );
+        assert_eq!(parse_response(TOO_SOON), Ok((Outcome::TooSoon, Some(65))));
+        assert_eq!(
+            parse_response(WRONG_LEVEL),
+            Ok((Outcome::WrongLevel, Some(0)))
+        );
+        assert_eq!(parse_response(FULL_PAGE), Ok((Outcome::TooLow, Some(600))));
+        assert!(parse_response("<html>Log in</html>").is_err());
+        // A cooldown that cannot be read is left unknown, not guessed
+        assert_eq!(parse_response(UNREADABLE), Ok((Outcome::Wrong, None)));
+        let outside = "<p>Please wait 5 minutes</p><article><p>That's not the right \
+            answer.</p></article>";
+        assert_eq!(parse_response(outside), Ok((Outcome::Wrong, None)));
+        let too_soon = "<article><p>You gave an answer too recently.</p></article>";
+        assert!(parse_response(too_soon).is_err());
+    }
+
+    #[test]
+    fn wrong_answers_are_never_resent() {
+        let server = StubServer::start(&[
+            (200, TOO_HIGH),
+            (200, TOO_SOON),
+            (200, RIGHT),
+            (200, UNREADABLE),
+        ]);
+        let client = Client::new(server.url(), "token");
+        let mut history = temp_history("wrong");
+
+        let start = 1_700_000_000;
+        assert_eq!(
+            submit(&client, &mut history, "day1", "part2", "100", start),
+            Ok(Outcome::TooHigh)
+        );
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/day/1/answer");
+        assert_eq!(requests[0].body, "level=2&answer=100");
+        assert_eq!(requests[0].header("Cookie"), Some("session=token"));
+
+        // Refused without asking the server
+        assert!(submit(&client, &mut history, "day1", "part2", "100", start + 600).is_err());
+        assert!(submit(&client, &mut history, "day1", "part2", "150", start + 600).is_err());
+        let too_soon = submit(&client, &mut history, "day1", "part2", "90", start + 10);
+        assert_eq!(
+            too_soon,
+            Err("Submitting too soon, wait 50s more".to_string())
+        );
+        assert_eq!(server.requests().len(), 1);
+
+        // The history survives reloading
+        let mut history = History::load(history.path.parent().unwrap()).unwrap();
+        assert_eq!(
+            submit(&client, &mut history, "day1", "part2", "90", start + 60),
+            Ok(Outcome::TooSoon)
+        );
+        assert!(submit(&client, &mut history, "day1", "part2", "90", start + 100).is_err());
+        assert_eq!(
+            submit(&client, &mut history, "day1", "part2", "90", start + 125),
+            Ok(Outcome::Correct)
+        );
+        assert!(submit(&client, &mut history, "day1", "part2", "90", start + 999).is_err());
+        assert_eq!(server.requests().len(), 3);
+
+        // A wrong answer is recorded even when its wait cannot be read
+        let later = start + 1000;
+        assert_eq!(
+            submit(&client, &mut history, "day2", "part1", "7", later),
+            Err(
+                "7 was wrong, but the wait could not be read from the response, \
+                so 900s is assumed"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            submit(&client, &mut history, "day2", "part1", "8", later + 60),
+            Err("Submitting too soon, wait 840s more".to_string())
+        );
+        let mut history = History::load(history.path.parent().unwrap()).unwrap();
+        assert!(submit(&client, &mut history, "day2", "part1", "7", later + 999).is_err());
+        assert_eq!(server.requests().len(), 4);
+        fs::remove_dir_all(history.path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::PathBuf,
+    process, thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{answers::Answers, fetch, solve};
+
+/// Modification time of every file in a day's directory.
+type Snapshot = BTreeMap<String, SystemTime>;
+
+fn snapshot(dir: &str) -> Result<Snapshot, String> {
+    let mut files = BTreeMap::new();
+    for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir, e))? {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", dir, e))?;
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+        files.insert(entry.file_name().to_string_lossy().to_string(), modified);
+    }
+    Ok(files)
+}
+
+/// Files added, removed or modified between two snapshots, by name.
+fn changes(before: &Snapshot, after: &Snapshot) -> Vec<String> {
+    let mut changed: Vec<String> = after
+        .iter()
+        .filter(|(name, modified)| before.get(*name) != Some(modified))
+        .map(|(name, _)| name.clone())
+        .collect();
+    changed.extend(
+        before
+            .keys()
+            .filter(|name| !after.contains_key(*name))
+            .cloned(),
+    );
+    changed.sort();
+    changed
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Status {
+    Correct,
+    Wrong(String),
+    Unknown,
+    Failed(String),
+}
+
+/// One part run on one input file.
+struct Check {
+    part: &'static str,
+    file: String,
+    answer: Option<String>,
+    status: Status,
+    time: Duration,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  {} {:<22} {:>16}  {:>9.1?}  ",
+            self.part,
+            self.file,
+            self.answer.as_deref().unwrap_or("-"),
+            self.time
+        )?;
+        match &self.status {
+            Status::Correct => write!(f, "ok"),
+            Status::Wrong(expected) => write!(f, "WRONG, expected {}", expected),
+            Status::Unknown => write!(f, "no expected answer"),
+            Status::Failed(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
+/// Runs each part on its sample and on the real input at `input`, when they
+/// exist, checking the answers against the day's answers.txt.
+fn check(day: &str, input: &str) -> Vec<Check> {
+    let answers = Answers::load(day).unwrap_or_default();
+    let mut checks = Vec::new();
+    for part in ["part1", "part2"] {
+        for file in [
+            format!("{}_sample_input.txt", part),
+            "input.txt".to_string(),
+        ] {
+            let path = match file.as_str() {
+                "input.txt" => input.to_string(),
+                _ => format!("src/{}/{}", day, file),
+            };
+            if !std::path::Path::new(&path).exists() {
+                continue;
+            }
+            let start = Instant::now();
+            let result = solve(day, part, &path);
+            let time = start.elapsed();
+            let (answer, status) = match result {
+                Ok(answer) => {
+                    let status = match answers.expected(part, &file) {
+                        Some(expected) if expected == answer => Status::Correct,
+                        Some(expected) => Status::Wrong(expected.to_string()),
+                        None => Status::Unknown,
+                    };
+                    (Some(answer), status)
+                }
+                Err(e) => (None, Status::Failed(e)),
+            };
+            checks.push(Check {
+                part,
+                file,
+                answer,
+                status,
+                time,
+            });
+        }
+    }
+    checks
+}
+
+fn print_checks(day: &str) {
+    for check in check(day, &fetch::input_path(day)) {
+        println!("{}", check);
+    }
+}
+
+/// Builds with cargo and, if that works, replaces this process with the new
+/// binary run with the same arguments.
+fn rebuild_and_restart(exe: &PathBuf) -> Result<(), String> {
+    let mut build = process::Command::new("cargo");
+    build.arg("build");
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    let status = build
+        .status()
+        .map_err(|e| format!("Failed to run cargo: {}", e))?;
+    if !status.success() {
+        return Err(format!("cargo build failed: {}", status));
+    }
+
+    let mut restart = process::Command::new(exe);
+    restart.args(std::env::args_os().skip(1));
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(format!("Failed to restart: {}", restart.exec()))
+    }
+    #[cfg(not(unix))]
+    {
+        let status = restart
+            .status()
+            .map_err(|e| format!("Failed to restart: {}", e))?;
+        process::exit(status.code().unwrap_or(1));
+    }
+}
+
+/// Checks `day` now and again whenever a file in `src/<day>/` changes,
+/// polling every `interval`. Source changes only take effect with `rebuild`,
+/// which rebuilds and restarts the watcher on the new code.
+pub fn watch(day: &str, interval: Duration, rebuild: bool) -> Result<(), String> {
+    let dir = format!("src/{}", day);
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find own binary: {}", e))?;
+    let mut before = snapshot(&dir)?;
+    println!("Watching {}", dir);
+    print_checks(day);
+
+    loop {
+        thread::sleep(interval);
+        let after = snapshot(&dir)?;
+        let changed = changes(&before, &after);
+        before = after;
+        if changed.is_empty() {
+            continue;
+        }
+
+        println!("-- changed: {}", changed.join(", "));
+        if changed.iter().any(|name| name.ends_with(".rs")) {
+            if rebuild {
+                // Only returns if the build or restart failed
+                let e = rebuild_and_restart(&exe).unwrap_err();
+                println!("{}, still running the old build", e);
+            } else {
+                println!("Source changed, rerun with --rebuild to pick it up");
+            }
+        }
+        print_checks(day);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_lists_added_removed_and_modified() {
+        let at = |secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let before: Snapshot = [("a.txt", at(1)), ("b.txt", at(1)), ("mod.rs", at(1))]
+            .into_iter()
+            .map(|(name, time)| (name.to_string(), time))
+            .collect();
+        let after: Snapshot = [("a.txt", at(1)), ("c.txt", at(2)), ("mod.rs", at(3))]
+            .into_iter()
+            .map(|(name, time)| (name.to_string(), time))
+            .collect();
+        assert_eq!(changes(&before, &after), vec!["b.txt", "c.txt", "mod.rs"]);
+        assert!(changes(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn check_verifies_against_answers() {
+        let checks = check("day1", "src/day1/input.txt");
+        assert_eq!(checks.len(), 4);
+        for check in checks.iter() {
+            assert_eq!(check.status, Status::Correct, "{}", check);
+        }
+    }
+}